@@ -1,23 +1,61 @@
 use std::{
     fs,
+    io::ErrorKind,
+    net::IpAddr,
+    num::NonZeroUsize,
     path::{Path, PathBuf},
 };
 
-use clap::{ArgAction, Parser, ValueHint};
+use clap::{
+    ArgMatches, CommandFactory, FromArgMatches, Parser, ValueEnum, ValueHint,
+    builder::BoolishValueParser, parser::ValueSource,
+};
+use ipnet::{AddrParseError, IpNet};
+use serde::{Deserialize, Deserializer, de};
 
-use crate::error::{Error, Result};
+use crate::{
+    error::{Error, Result},
+    header::{self, HeaderField},
+    redirect,
+};
 
 /// Configuration data for Holo.
 pub struct Config {
     /// The command line arguments providing configuration data.
     args: Args,
+
+    /// The redirect and rewrite [`Rule`][redirect::Rule]s in order of
+    /// precedence.
+    redirect_rules: Vec<redirect::Rule>,
+
+    /// The [`Rule`][header::Rule]s for sending custom HTTP response header
+    /// fields.
+    header_rules: Vec<header::Rule>,
 }
 
 impl Config {
-    /// Creates new configuration data from command line arguments.
+    /// Creates new configuration data from command line arguments, environment
+    /// variables, and an optional config file. Command line arguments take
+    /// precedence over environment variables, which take precedence over the
+    /// config file. Redirect and header rules are loaded from the root
+    /// directory's rules files, followed by the config file.
     pub fn try_new() -> Result<Self> {
-        let args = Args::try_parse().map_err(Error::Command)?;
-        Ok(Self { args })
+        let matches = Args::command().try_get_matches().map_err(Error::Command)?;
+        let mut args = Args::from_arg_matches(&matches).map_err(Error::Command)?;
+        let mut redirect_rules = redirect::try_load_rules(&args.root)?;
+        let mut header_rules = header::try_load_rules(&args.root)?;
+
+        if let Some(mut file) = ConfigFile::try_load(&args)? {
+            redirect_rules.append(&mut file.redirects);
+            header_rules.append(&mut file.headers);
+            file.merge(&mut args, &matches);
+        }
+
+        Ok(Self {
+            args,
+            redirect_rules,
+            header_rules,
+        })
     }
 
     /// Returns the canonical [`Path`] to the root directory for serving files.
@@ -25,23 +63,171 @@ impl Config {
         &self.args.root
     }
 
+    /// Returns the IP addresses to listen on.
+    pub fn bind_addresses(&self) -> &[IpAddr] {
+        &self.args.bind_addresses
+    }
+
+    /// Returns the IP networks of clients other than the host machine that are
+    /// allowed to request files.
+    pub fn allowed_networks(&self) -> &[IpNet] {
+        &self.args.allowed_networks
+    }
+
+    /// Returns the host names that requests may be directed at in addition to
+    /// the host machine.
+    pub fn allowed_hosts(&self) -> &[String] {
+        &self.args.allowed_hosts
+    }
+
     /// Returns the desired TCP port.
     pub fn port(&self) -> u16 {
         self.args.port
     }
 
-    /// Returns whether to serve automatic index pages.
+    /// Returns the number of successive TCP ports to try if the desired port is
+    /// in use before using an OS-assigned port. Returns [`None`] if port
+    /// fallback is disabled.
+    pub fn port_fallback_attempts(&self) -> Option<u16> {
+        self.args.port_fallback_attempts
+    }
+
+    /// Returns the path to write information about the server to once it is
+    /// listening, if any.
+    pub fn ready_file(&self) -> Option<&Path> {
+        self.args.ready_file.as_deref()
+    }
+
+    /// Returns the [`AddressFormat`] for printing the server's addresses.
+    pub fn address_format(&self) -> AddressFormat {
+        self.args.address_format
+    }
+
+    /// Returns the [`LogFormat`] for access logging. Returns [`None`] if access
+    /// logging is disabled.
+    pub fn log_format(&self) -> Option<LogFormat> {
+        // Log files are machine-readable by default.
+        self.args
+            .log_format
+            .or_else(|| self.args.log_file.is_some().then_some(LogFormat::Combined))
+    }
+
+    /// Returns the path to the file to write access log entries to, if any.
+    pub fn log_file(&self) -> Option<&Path> {
+        self.args.log_file.as_deref()
+    }
+
+    /// Returns the path to a fallback document relative to the root directory
+    /// for single-page application routes. Returns [`None`] if single-page
+    /// application fallback routing is disabled.
+    pub fn spa_fallback(&self) -> Option<&Path> {
+        self.args.spa_fallback.as_deref()
+    }
+
+    /// Returns the path to the directory containing custom error documents
+    /// relative to the root directory. Returns [`None`] if custom error
+    /// documents are in the root directory.
+    pub fn error_dir(&self) -> Option<&Path> {
+        self.args.error_dir.as_deref()
+    }
+
+    /// Returns the file extensions to try in order for clean URLs. Returns
+    /// [`None`] if clean URLs are disabled.
+    pub fn clean_url_extensions(&self) -> Option<&[String]> {
+        self.args.clean_url_extensions.as_deref()
+    }
+
+    /// Returns whether to redirect URIs with clean URL extensions to their
+    /// clean URLs.
+    pub fn is_redirecting_clean_urls(&self) -> bool {
+        self.args.is_redirecting_clean_urls
+    }
+
+    /// Returns the number of worker threads for serving connections.
+    pub fn threads(&self) -> NonZeroUsize {
+        self.args.threads
+    }
+
+    /// Returns the names of index files to serve for directories, in order of
+    /// preference.
+    pub fn index_files(&self) -> &[String] {
+        &self.args.index_files
+    }
+
+    /// Returns whether to serve automatic index pages for directories without
+    /// an index file.
     pub fn is_serving_index_pages(&self) -> bool {
         self.args.is_serving_index_pages
     }
 
+    /// Returns the redirect and rewrite [`Rule`][redirect::Rule]s in order of
+    /// precedence.
+    pub fn redirect_rules(&self) -> &[redirect::Rule] {
+        &self.redirect_rules
+    }
+
+    /// Returns whether to compress compressible responses on the fly.
+    pub fn is_compressing(&self) -> bool {
+        self.args.is_compressing
+    }
+
+    /// Returns whether to watch the root directory and reload pages when files
+    /// change.
+    pub fn is_watching(&self) -> bool {
+        self.args.is_watching
+    }
+
     /// Returns whether to serve HTTP response header fields for cross-origin
     /// isolation.
     pub fn is_cross_origin_isolated(&self) -> bool {
-        self.args.is_cross_origin_isolated
+        !self.args.is_isolation_disabled
+    }
+
+    /// Returns an iterator over the custom [`HeaderField`]s to send in a
+    /// response, optionally to a request with a decoded URI. Header fields for
+    /// every response come first, followed by header fields from each
+    /// [`Rule`][header::Rule] matching the URI in order.
+    pub fn header_fields<'a>(
+        &'a self,
+        uri: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a HeaderField> {
+        let rules = self
+            .header_rules
+            .iter()
+            .filter(move |rule| uri.is_some_and(|uri| rule.is_match(uri)));
+
+        self.args
+            .header_fields
+            .iter()
+            .chain(rules.flat_map(header::Rule::fields))
     }
 }
 
+/// A format for printing the server's addresses.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFormat {
+    /// Human-readable text.
+    Text,
+
+    /// A line of JSON for scripts and test runners.
+    Json,
+}
+
+/// A format for access log entries.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// The Combined Log Format, followed by the duration in microseconds.
+    Combined,
+
+    /// A compact format for developers, colored if logging to a terminal.
+    Dev,
+
+    /// JSON lines.
+    Json,
+}
+
 /// Command line arguments.
 #[derive(Parser)]
 #[command(bin_name("holo"), version, about)]
@@ -51,26 +237,507 @@ struct Args {
         value_hint(ValueHint::DirPath),
         value_parser = parse_root,
         help = "Server root directory",
+        env = "HOLO_ROOT",
         default_value = ".",
     )]
     root: PathBuf,
 
+    /// The IP addresses to listen on.
+    #[arg(
+        id = "bind",
+        value_name = "ADDRESS",
+        value_delimiter = ',',
+        help = "IP address to listen on",
+        short,
+        long,
+        env = "HOLO_BIND",
+        default_value = "127.0.0.1"
+    )]
+    bind_addresses: Vec<IpAddr>,
+
+    /// The IP networks of clients other than the host machine that are allowed
+    /// to request files.
+    #[arg(
+        id = "allow",
+        value_name = "NETWORK",
+        value_parser = parse_network,
+        value_delimiter = ',',
+        help = "Allow clients from an IP address or CIDR range",
+        long,
+        env = "HOLO_ALLOW",
+    )]
+    allowed_networks: Vec<IpNet>,
+
+    /// The host names that requests may be directed at in addition to the host
+    /// machine.
+    #[arg(
+        id = "allowed-host",
+        value_name = "HOST",
+        value_delimiter = ',',
+        help = "Allow requests directed at a host name",
+        long,
+        env = "HOLO_ALLOWED_HOST"
+    )]
+    allowed_hosts: Vec<String>,
+
     /// The desired TCP port.
-    #[arg(help = "TCP port", short, long, default_value_t = 8080)]
+    #[arg(
+        help = "TCP port",
+        short,
+        long,
+        env = "HOLO_PORT",
+        default_value_t = 8080
+    )]
     port: u16,
 
-    /// Whether to serve automatic index pages.
-    #[arg(id = "index", help = "Serve automatic index pages", short, long)]
+    /// The number of successive TCP ports to try if the desired port is in use
+    /// before using an OS-assigned port, if port fallback is enabled.
+    #[arg(
+        id = "port-fallback",
+        value_name = "ATTEMPTS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "10",
+        help = "Try the next ports if the port is in use [default: 10]",
+        long,
+        env = "HOLO_PORT_FALLBACK"
+    )]
+    port_fallback_attempts: Option<u16>,
+
+    /// The path to write information about the server to once it is listening.
+    #[arg(
+        id = "ready-file",
+        value_name = "PATH",
+        value_hint(ValueHint::FilePath),
+        help = "Write the server's addresses as JSON once it is listening",
+        long,
+        env = "HOLO_READY_FILE"
+    )]
+    ready_file: Option<PathBuf>,
+
+    /// The format for printing the server's addresses.
+    #[arg(
+        id = "print-address",
+        value_name = "FORMAT",
+        help = "Format for printing the server's addresses",
+        long,
+        env = "HOLO_PRINT_ADDRESS",
+        default_value = "text"
+    )]
+    address_format: AddressFormat,
+
+    /// The format for access logging, if access logging is enabled.
+    #[arg(
+        id = "log",
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "dev",
+        help = "Log each request [default: dev]",
+        long,
+        env = "HOLO_LOG"
+    )]
+    log_format: Option<LogFormat>,
+
+    /// The path to the file to write access log entries to.
+    #[arg(
+        id = "log-file",
+        value_name = "PATH",
+        value_hint(ValueHint::FilePath),
+        help = "Append log entries to a file instead of stdout",
+        long,
+        env = "HOLO_LOG_FILE"
+    )]
+    log_file: Option<PathBuf>,
+
+    /// The number of worker threads for serving connections.
+    #[arg(
+        help = "Number of worker threads",
+        short,
+        long,
+        env = "HOLO_THREADS",
+        default_value = "8"
+    )]
+    threads: NonZeroUsize,
+
+    /// Whether to serve automatic index pages for directories without an index
+    /// file.
+    #[arg(
+        id = "index",
+        value_parser = BoolishValueParser::new(),
+        help = "Serve automatic index pages",
+        short,
+        long,
+        env = "HOLO_INDEX"
+    )]
     is_serving_index_pages: bool,
 
-    /// Whether to serve HTTP response header fields for cross-origin isolation.
+    /// The names of index files to serve for directories, in order of
+    /// preference.
+    #[arg(
+        id = "index-file",
+        value_name = "NAMES",
+        value_delimiter = ',',
+        help = "Index file names in order of preference",
+        long,
+        env = "HOLO_INDEX_FILE",
+        default_value = "index.html"
+    )]
+    index_files: Vec<String>,
+
+    /// The path to a fallback document relative to the root directory for
+    /// single-page application routes.
+    #[arg(
+        id = "spa",
+        value_name = "FALLBACK",
+        value_hint(ValueHint::FilePath),
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "index.html",
+        help = "Serve a fallback document for unresolved routes [default: index.html]",
+        long,
+        env = "HOLO_SPA"
+    )]
+    spa_fallback: Option<PathBuf>,
+
+    /// The file extensions to try in order for clean URLs, if clean URLs are
+    /// enabled.
+    #[arg(
+        id = "clean-urls",
+        value_name = "EXTENSIONS",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        default_missing_value = "html",
+        help = "Serve files without their extensions [default: html]",
+        long,
+        env = "HOLO_CLEAN_URLS"
+    )]
+    clean_url_extensions: Option<Vec<String>>,
+
+    /// Whether to redirect URIs with clean URL extensions to their clean URLs.
+    #[arg(
+        id = "clean-redirect",
+        value_parser = BoolishValueParser::new(),
+        help = "Redirect URIs with clean URL extensions",
+        long,
+        env = "HOLO_CLEAN_REDIRECT"
+    )]
+    is_redirecting_clean_urls: bool,
+
+    /// The path to the directory containing custom error documents relative to
+    /// the root directory.
+    #[arg(
+        id = "error-dir",
+        value_name = "DIR",
+        value_hint(ValueHint::DirPath),
+        help = "Directory of custom error pages in the root [default: ROOT]",
+        long,
+        env = "HOLO_ERROR_DIR"
+    )]
+    error_dir: Option<PathBuf>,
+
+    /// Whether to compress compressible responses on the fly.
+    #[arg(
+        id = "compress",
+        value_parser = BoolishValueParser::new(),
+        help = "Compress text responses",
+        short,
+        long,
+        env = "HOLO_COMPRESS"
+    )]
+    is_compressing: bool,
+
+    /// Whether to watch the root directory and reload pages when files change.
+    #[arg(
+        id = "watch",
+        value_parser = BoolishValueParser::new(),
+        help = "Reload pages when files change",
+        short,
+        long,
+        env = "HOLO_WATCH"
+    )]
+    is_watching: bool,
+
+    /// Whether to disable HTTP response header fields for cross-origin
+    /// isolation.
     #[arg(
         id = "no-isolation",
-        action = ArgAction::SetFalse,
+        value_parser = BoolishValueParser::new(),
         help = "Disable cross-origin isolation",
         long,
+        env = "HOLO_NO_ISOLATION",
+    )]
+    is_isolation_disabled: bool,
+
+    /// The custom HTTP header fields to send in every response.
+    #[arg(
+        id = "header",
+        value_name = "FIELD",
+        value_parser = header::parse_field,
+        help = "Send a custom HTTP response header field",
+        long,
+        env = "HOLO_HEADER"
     )]
-    is_cross_origin_isolated: bool,
+    header_fields: Vec<HeaderField>,
+
+    /// The path to a config file to load options from.
+    #[arg(
+        id = "config",
+        value_name = "PATH",
+        value_hint(ValueHint::FilePath),
+        help = "Load options from a TOML file instead of ROOT/holo.toml",
+        long,
+        env = "HOLO_CONFIG"
+    )]
+    config_path: Option<PathBuf>,
+}
+
+/// Options loaded from a TOML config file. Each key matches the long name of a
+/// command line option.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct ConfigFile {
+    /// The IP addresses to listen on.
+    bind: Option<Vec<IpAddr>>,
+
+    /// The IP networks of clients other than the host machine that are allowed
+    /// to request files.
+    #[serde(default, deserialize_with = "deserialize_networks")]
+    allow: Option<Vec<IpNet>>,
+
+    /// The host names that requests may be directed at in addition to the host
+    /// machine.
+    allowed_host: Option<Vec<String>>,
+
+    /// The desired TCP port.
+    port: Option<u16>,
+
+    /// The number of successive TCP ports to try if the desired port is in use
+    /// before using an OS-assigned port.
+    port_fallback: Option<u16>,
+
+    /// The path to write information about the server to once it is listening.
+    ready_file: Option<PathBuf>,
+
+    /// The format for printing the server's addresses.
+    print_address: Option<AddressFormat>,
+
+    /// The format for access logging.
+    log: Option<LogFormat>,
+
+    /// The path to the file to write access log entries to.
+    log_file: Option<PathBuf>,
+
+    /// The number of worker threads for serving connections.
+    threads: Option<NonZeroUsize>,
+
+    /// Whether to serve automatic index pages for directories without an index
+    /// file.
+    index: Option<bool>,
+
+    /// The names of index files to serve for directories, in order of
+    /// preference.
+    index_file: Option<Vec<String>>,
+
+    /// The path to a fallback document relative to the root directory for
+    /// single-page application routes.
+    spa: Option<PathBuf>,
+
+    /// The path to the directory containing custom error documents relative to
+    /// the root directory.
+    error_dir: Option<PathBuf>,
+
+    /// The file extensions to try in order for clean URLs.
+    clean_urls: Option<Vec<String>>,
+
+    /// Whether to redirect URIs with clean URL extensions to their clean URLs.
+    clean_redirect: Option<bool>,
+
+    /// Whether to compress compressible responses on the fly.
+    compress: Option<bool>,
+
+    /// Whether to watch the root directory and reload pages when files change.
+    watch: Option<bool>,
+
+    /// Whether to disable cross-origin isolation.
+    no_isolation: Option<bool>,
+
+    /// The custom HTTP header fields to send in every response.
+    header: Option<Vec<HeaderField>>,
+
+    /// The redirect and rewrite [`Rule`]s that follow the redirect rules file's
+    /// rules.
+    #[serde(default)]
+    redirects: Vec<redirect::Rule>,
+
+    /// The rules for sending custom HTTP response header fields that follow
+    /// the header rules file's rules.
+    #[serde(default)]
+    headers: Vec<header::Rule>,
+}
+
+impl ConfigFile {
+    /// The name of the config file that is loaded from the root directory if
+    /// no config file path is given.
+    const DEFAULT_NAME: &str = "holo.toml";
+
+    /// Loads a `ConfigFile` from the path given by command line arguments, or
+    /// from the root directory. Returns [`None`] if no path was given and the
+    /// root directory does not contain a config file.
+    fn try_load(args: &Args) -> Result<Option<Self>> {
+        let source = match &args.config_path {
+            Some(path) => fs::read_to_string(path).map_err(Error::ConfigRead)?,
+            None => match fs::read_to_string(args.root.join(Self::DEFAULT_NAME)) {
+                Ok(source) => source,
+                Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(error) => return Err(Error::ConfigRead(error)),
+            },
+        };
+
+        toml::from_str(&source).map(Some).map_err(|error| {
+            // Lines and columns are counted from 1 in characters.
+            let index = error.span().map_or(0, |span| span.start);
+            let before = source.get(..index).unwrap_or(&source);
+            let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+            Error::ConfigInvalid {
+                line: before.matches('\n').count() + 1,
+                column: before[line_start..].chars().count() + 1,
+                message: error.message().into(),
+            }
+        })
+    }
+
+    /// Merges the `ConfigFile` into [`Args`] for each option that was not set
+    /// by a command line argument or an environment variable.
+    fn merge(self, args: &mut Args, matches: &ArgMatches) {
+        let is_unset = |id| {
+            matches
+                .value_source(id)
+                .is_none_or(|source| source == ValueSource::DefaultValue)
+        };
+
+        if let Some(bind) = self.bind
+            && is_unset("bind")
+        {
+            args.bind_addresses = bind;
+        }
+
+        if let Some(allow) = self.allow
+            && is_unset("allow")
+        {
+            args.allowed_networks = allow;
+        }
+
+        if let Some(allowed_host) = self.allowed_host
+            && is_unset("allowed-host")
+        {
+            args.allowed_hosts = allowed_host;
+        }
+
+        if let Some(port) = self.port
+            && is_unset("port")
+        {
+            args.port = port;
+        }
+
+        if let Some(port_fallback) = self.port_fallback
+            && is_unset("port-fallback")
+        {
+            args.port_fallback_attempts = Some(port_fallback);
+        }
+
+        if let Some(ready_file) = self.ready_file
+            && is_unset("ready-file")
+        {
+            args.ready_file = Some(ready_file);
+        }
+
+        if let Some(print_address) = self.print_address
+            && is_unset("print-address")
+        {
+            args.address_format = print_address;
+        }
+
+        if let Some(log) = self.log
+            && is_unset("log")
+        {
+            args.log_format = Some(log);
+        }
+
+        if let Some(log_file) = self.log_file
+            && is_unset("log-file")
+        {
+            args.log_file = Some(log_file);
+        }
+
+        if let Some(threads) = self.threads
+            && is_unset("threads")
+        {
+            args.threads = threads;
+        }
+
+        if let Some(index) = self.index
+            && is_unset("index")
+        {
+            args.is_serving_index_pages = index;
+        }
+
+        if let Some(index_file) = self.index_file
+            && is_unset("index-file")
+        {
+            args.index_files = index_file;
+        }
+
+        if let Some(spa) = self.spa
+            && is_unset("spa")
+        {
+            args.spa_fallback = Some(spa);
+        }
+
+        if let Some(clean_urls) = self.clean_urls
+            && is_unset("clean-urls")
+        {
+            args.clean_url_extensions = Some(clean_urls);
+        }
+
+        if let Some(clean_redirect) = self.clean_redirect
+            && is_unset("clean-redirect")
+        {
+            args.is_redirecting_clean_urls = clean_redirect;
+        }
+
+        if let Some(error_dir) = self.error_dir
+            && is_unset("error-dir")
+        {
+            args.error_dir = Some(error_dir);
+        }
+
+        if let Some(compress) = self.compress
+            && is_unset("compress")
+        {
+            args.is_compressing = compress;
+        }
+
+        if let Some(watch) = self.watch
+            && is_unset("watch")
+        {
+            args.is_watching = watch;
+        }
+
+        if let Some(no_isolation) = self.no_isolation
+            && is_unset("no-isolation")
+        {
+            args.is_isolation_disabled = no_isolation;
+        }
+
+        if let Some(header) = self.header
+            && is_unset("header")
+        {
+            args.header_fields = header;
+        }
+    }
 }
 
 /// Parses a canonical path to a root directory.
@@ -83,3 +750,22 @@ fn parse_root(root: &str) -> Result<PathBuf> {
 
     Ok(root)
 }
+
+/// Parses an IP network from an IP address or a CIDR range.
+fn parse_network(network: &str) -> std::result::Result<IpNet, AddrParseError> {
+    network
+        .parse::<IpAddr>()
+        .map(IpNet::from)
+        .or_else(|_| network.parse())
+}
+
+/// Deserializes a list of IP networks from IP addresses and CIDR ranges.
+fn deserialize_networks<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Vec<IpNet>>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|network| parse_network(network).map_err(de::Error::custom))
+        .collect::<std::result::Result<_, _>>()
+        .map(Some)
+}
@@ -6,6 +6,8 @@ use std::{
     result,
 };
 
+use crate::http::Status;
+
 /// A specialized [`Result`][result::Result] type for Holo.
 pub type Result<T> = result::Result<T, Error>;
 
@@ -15,6 +17,45 @@ pub enum Error {
     /// A command line argument parsing error or a help or version message.
     Command(clap::Error),
 
+    /// An `Error` caused by failing to read a config file.
+    ConfigRead(io::Error),
+
+    /// An `Error` caused by a config file being invalid at a line and column.
+    ConfigInvalid {
+        /// The line of the `Error`.
+        line: usize,
+
+        /// The column of the `Error`.
+        column: usize,
+
+        /// The message describing the `Error`.
+        message: String,
+    },
+
+    /// An `Error` caused by failing to read the redirect rules file.
+    RedirectsRead(io::Error),
+
+    /// An `Error` caused by the redirect rules file being invalid at a line.
+    RedirectsInvalid {
+        /// The line of the `Error`.
+        line: usize,
+
+        /// The message describing the `Error`.
+        message: String,
+    },
+
+    /// An `Error` caused by failing to read the header rules file.
+    HeadersRead(io::Error),
+
+    /// An `Error` caused by the header rules file being invalid at a line.
+    HeadersInvalid {
+        /// The line of the `Error`.
+        line: usize,
+
+        /// The message describing the `Error`.
+        message: String,
+    },
+
     /// An `Error` caused by the root [`Path`][std::path::Path] not existing.
     RootNotExist(io::Error),
 
@@ -25,18 +66,44 @@ pub enum Error {
     /// An `Error` caused by failing to open a [`Server`][crate::http::Server].
     ServerOpen(io::Error),
 
+    /// An `Error` caused by a TCP port already being in use.
+    ServerPortInUse(u16),
+
     /// An `Error` caused by failing to query a
     /// [`Server`][crate::http::Server]'s TCP/IP address.
     ServerAddressQuery(io::Error),
 
+    /// An `Error` caused by failing to write the ready file.
+    ReadyFileWrite(io::Error),
+
+    /// An `Error` caused by failing to open the log file.
+    LogFileOpen(io::Error),
+
+    /// An `Error` caused by failing to set the shutdown signal handler.
+    SignalHandler(ctrlc::Error),
+
     /// An `Error` caused by failing to establish a connection with a client.
     Connect(io::Error),
 
     /// An `Error` caused by failing to read a request.
     RequestRead(io::Error),
 
-    /// An `Error` caused by a request not being an HTTP GET request.
-    RequestNotHttpGet,
+    /// An `Error` caused by a request not being a well-formed HTTP request.
+    RequestMalformed,
+
+    /// An `Error` caused by a request's target being too long.
+    RequestUriTooLong,
+
+    /// An `Error` caused by a request's header fields being too large or too
+    /// numerous.
+    RequestHeaderTooLarge,
+
+    /// An `Error` caused by a request using an unsupported HTTP version.
+    RequestVersionNotSupported,
+
+    /// An `Error` caused by a request's `Host` header field naming a host that
+    /// is not allowed.
+    RequestHostNotAllowed,
 
     /// An `Error` caused by failing to read a file.
     FileRead(io::Error),
@@ -46,6 +113,9 @@ pub enum Error {
 
     /// An `Error` caused by failing to send an HTTP response.
     ResponseSend(io::Error),
+
+    /// An `Error` caused by failing to watch the root directory for changes.
+    Watch(notify::Error),
 }
 
 impl Error {
@@ -58,6 +128,19 @@ impl Error {
         };
     }
 
+    /// Returns the HTTP response [`Status`] to send to a client for the `Error`.
+    /// Returns [`None`] if the `Error` is not caused by the client.
+    pub fn status(&self) -> Option<Status> {
+        match self {
+            Self::RequestMalformed => Some(Status::BadRequest),
+            Self::RequestUriTooLong => Some(Status::UriTooLong),
+            Self::RequestHeaderTooLarge => Some(Status::RequestHeaderFieldsTooLarge),
+            Self::RequestVersionNotSupported => Some(Status::HttpVersionNotSupported),
+            Self::RequestHostNotAllowed => Some(Status::MisdirectedRequest),
+            _ => None,
+        }
+    }
+
     /// Returns the [`ExitCode`] associated with the `Error`.
     pub fn exit_code(&self) -> ExitCode {
         match self {
@@ -71,15 +154,31 @@ impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             Self::Command(error) => Some(error),
-            Self::RootNotExist(error)
+            Self::ConfigRead(error)
+            | Self::RedirectsRead(error)
+            | Self::HeadersRead(error)
+            | Self::RootNotExist(error)
             | Self::ServerOpen(error)
             | Self::ServerAddressQuery(error)
+            | Self::ReadyFileWrite(error)
+            | Self::LogFileOpen(error)
             | Self::Connect(error)
             | Self::RequestRead(error)
             | Self::FileRead(error)
             | Self::DirRead(error)
             | Self::ResponseSend(error) => Some(error),
-            Self::RootNotDirectory | Self::RequestNotHttpGet => None,
+            Self::SignalHandler(error) => Some(error),
+            Self::Watch(error) => Some(error),
+            Self::ConfigInvalid { .. }
+            | Self::RedirectsInvalid { .. }
+            | Self::HeadersInvalid { .. }
+            | Self::ServerPortInUse(_)
+            | Self::RootNotDirectory
+            | Self::RequestMalformed
+            | Self::RequestUriTooLong
+            | Self::RequestHeaderTooLarge
+            | Self::RequestVersionNotSupported
+            | Self::RequestHostNotAllowed => None,
         }
     }
 }
@@ -88,16 +187,48 @@ impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::Command(error) => error.fmt(f),
+            Self::ConfigRead(error) => write!(f, "failed to read config file: {error}"),
+            Self::ConfigInvalid {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "config file is invalid at line {line}, column {column}: {message}"
+            ),
+            Self::RedirectsRead(error) => write!(f, "failed to read redirect rules: {error}"),
+            Self::RedirectsInvalid { line, message } => {
+                write!(f, "redirect rules are invalid at line {line}: {message}")
+            }
+            Self::HeadersRead(error) => write!(f, "failed to read header rules: {error}"),
+            Self::HeadersInvalid { line, message } => {
+                write!(f, "header rules are invalid at line {line}: {message}")
+            }
             Self::RootNotExist(error) => write!(f, "root does not exist: {error}"),
             Self::RootNotDirectory => f.write_str("root is not a directory"),
             Self::ServerOpen(error) => write!(f, "failed to open server: {error}"),
+            Self::ServerPortInUse(port) => write!(
+                f,
+                "port {port} is already in use, use '--port' to choose another port or \
+                '--port-fallback' to find a free port"
+            ),
             Self::ServerAddressQuery(error) => write!(f, "failed to query server address: {error}"),
+            Self::ReadyFileWrite(error) => write!(f, "failed to write ready file: {error}"),
+            Self::LogFileOpen(error) => write!(f, "failed to open log file: {error}"),
+            Self::SignalHandler(error) => write!(f, "failed to set signal handler: {error}"),
             Self::Connect(error) => write!(f, "failed to connect: {error}"),
             Self::RequestRead(error) => write!(f, "failed to read request: {error}"),
-            Self::RequestNotHttpGet => f.write_str("request is not an HTTP GET request"),
+            Self::RequestMalformed => f.write_str("request is malformed"),
+            Self::RequestUriTooLong => f.write_str("request URI is too long"),
+            Self::RequestHeaderTooLarge => f.write_str("request header is too large"),
+            Self::RequestVersionNotSupported => {
+                f.write_str("request HTTP version is not supported")
+            }
+            Self::RequestHostNotAllowed => f.write_str("request host is not allowed"),
             Self::FileRead(error) => write!(f, "failed to read file: {error}"),
             Self::DirRead(error) => write!(f, "failed to read directory: {error}"),
             Self::ResponseSend(error) => write!(f, "failed to send response: {error}"),
+            Self::Watch(error) => write!(f, "failed to watch root: {error}"),
         }
     }
 }
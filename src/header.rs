@@ -0,0 +1,205 @@
+use std::{collections::BTreeMap, fs, io::ErrorKind, path::Path, result};
+
+use globset::{Glob, GlobMatcher};
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+
+/// The name of the header rules file that is loaded from the root directory.
+pub const RULES_FILE_NAME: &str = "_headers";
+
+/// The names of HTTP header fields that frame responses or are generated for
+/// each response, which may not be customized.
+const RESERVED_FIELD_NAMES: [&str; 12] = [
+    "Accept-Ranges",
+    "Allow",
+    "Connection",
+    "Content-Encoding",
+    "Content-Length",
+    "Content-Range",
+    "Content-Type",
+    "ETag",
+    "Last-Modified",
+    "Location",
+    "Transfer-Encoding",
+    "Vary",
+];
+
+/// A custom HTTP response header field.
+#[derive(Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct HeaderField {
+    /// The `HeaderField`'s name.
+    name: String,
+
+    /// The `HeaderField`'s value.
+    value: String,
+}
+
+impl HeaderField {
+    /// Creates a new `HeaderField` from a name and a value. Returns a message
+    /// describing why the header field is invalid on failure.
+    fn try_new(name: &str, value: &str) -> result::Result<Self, String> {
+        let is_token_char =
+            |char: char| char.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(char);
+
+        if name.is_empty() || !name.chars().all(is_token_char) {
+            return Err(format!("header field name '{name}' is invalid"));
+        }
+
+        if RESERVED_FIELD_NAMES
+            .iter()
+            .any(|reserved_name| reserved_name.eq_ignore_ascii_case(name))
+        {
+            return Err(format!("header field '{name}' may not be customized"));
+        }
+
+        let value = value.trim();
+
+        if value
+            .chars()
+            .any(|char| char.is_ascii_control() && char != '\t')
+        {
+            return Err(format!("header field '{name}' has an invalid value"));
+        }
+
+        Ok(Self {
+            name: name.into(),
+            value: value.into(),
+        })
+    }
+
+    /// Returns the `HeaderField`'s name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the `HeaderField`'s value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl TryFrom<String> for HeaderField {
+    type Error = String;
+
+    fn try_from(field: String) -> result::Result<Self, String> {
+        parse_field(&field)
+    }
+}
+
+/// A rule for sending custom [`HeaderField`]s for URIs matching a glob pattern.
+#[derive(Deserialize)]
+#[serde(try_from = "RuleTable")]
+pub struct Rule {
+    /// The [`GlobMatcher`] for matching decoded URIs.
+    matcher: GlobMatcher,
+
+    /// The [`HeaderField`]s to send.
+    fields: Vec<HeaderField>,
+}
+
+impl Rule {
+    /// Creates a new `Rule` without [`HeaderField`]s from a glob pattern.
+    /// Returns a message describing why the rule is invalid on failure.
+    fn try_new(pattern: &str) -> result::Result<Self, String> {
+        if !pattern.starts_with('/') {
+            return Err(format!("pattern '{pattern}' is not an absolute path"));
+        }
+
+        let matcher = Glob::new(pattern)
+            .map_err(|error| error.kind().to_string())?
+            .compile_matcher();
+
+        Ok(Self {
+            matcher,
+            fields: Vec::new(),
+        })
+    }
+
+    /// Returns whether the `Rule` applies to a decoded URI.
+    pub fn is_match(&self, uri: &str) -> bool {
+        self.matcher.is_match(uri)
+    }
+
+    /// Returns the `Rule`'s [`HeaderField`]s.
+    pub fn fields(&self) -> &[HeaderField] {
+        &self.fields
+    }
+}
+
+/// A [`Rule`] as a table in a config file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleTable {
+    /// The glob pattern.
+    #[serde(rename = "for")]
+    pattern: String,
+
+    /// The header field values keyed by name.
+    values: BTreeMap<String, String>,
+}
+
+impl TryFrom<RuleTable> for Rule {
+    type Error = String;
+
+    fn try_from(table: RuleTable) -> result::Result<Self, String> {
+        let mut rule = Self::try_new(&table.pattern)?;
+
+        for (name, value) in &table.values {
+            rule.fields.push(HeaderField::try_new(name, value)?);
+        }
+
+        Ok(rule)
+    }
+}
+
+/// Parses a [`HeaderField`] from a name and a value separated by a colon.
+pub fn parse_field(field: &str) -> result::Result<HeaderField, String> {
+    let Some((name, value)) = field.split_once(':') else {
+        return Err(format!("header field '{field}' has no value"));
+    };
+
+    HeaderField::try_new(name.trim(), value)
+}
+
+/// Loads [`Rule`]s from the header rules file in a root directory. Returns an
+/// empty [`Vec`] if the root directory does not contain a header rules file.
+pub fn try_load_rules(root: &Path) -> Result<Vec<Rule>> {
+    match fs::read_to_string(root.join(RULES_FILE_NAME)) {
+        Ok(source) => parse_rules(&source),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(Error::HeadersRead(error)),
+    }
+}
+
+/// Parses [`Rule`]s from the source of a header rules file. Each unindented
+/// line contains a glob pattern, followed by indented lines containing header
+/// fields. Blank lines and lines starting with `#` are ignored.
+fn parse_rules(source: &str) -> Result<Vec<Rule>> {
+    let mut rules: Vec<Rule> = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let trimmed_line = line.trim();
+
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        let result = if line.starts_with(char::is_whitespace) {
+            match rules.last_mut() {
+                None => Err("header field has no pattern".into()),
+                Some(rule) => parse_field(trimmed_line).map(|field| rule.fields.push(field)),
+            }
+        } else {
+            Rule::try_new(trimmed_line).map(|rule| rules.push(rule))
+        };
+
+        result.map_err(|message| Error::HeadersInvalid {
+            line: index + 1,
+            message,
+        })?;
+    }
+
+    Ok(rules)
+}
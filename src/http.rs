@@ -1,34 +1,90 @@
 use std::{
+    borrow::Cow,
     fmt::{self, Display, Formatter, Write as _},
-    io::{BufRead, BufReader, Read, Write},
-    net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    fs::File,
+    io::{self, BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream},
+    ops::Range,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
+mod compress;
+mod head;
+
 use percent_encoding::AsciiSet;
+use socket2::{Domain, Protocol, Socket, Type};
 
+use self::head::{HeaderFields, RequestHead};
+pub use self::head::{Method, Version};
 use crate::{
     config::Config,
     error::{Error, Result},
+    log::{LogEntry, Logger},
+    pool::ThreadPool,
 };
 
 /// An HTTP response status code.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[repr(u16)]
 pub enum Status {
     /// The request succeeded.
     Ok = 200,
 
+    /// The request succeeded and the body contains the requested ranges.
+    PartialContent = 206,
+
+    /// The URI of the requested resource has been changed permanently.
+    MovedPermanently = 301,
+
     /// The URI of the requested resource has been changed temporarily.
     Found = 302,
 
+    /// The client's cached copy of the requested resource is still valid.
+    NotModified = 304,
+
+    /// The URI of the requested resource has been changed temporarily and the
+    /// request method must not be changed.
+    TemporaryRedirect = 307,
+
+    /// The URI of the requested resource has been changed permanently and the
+    /// request method must not be changed.
+    PermanentRedirect = 308,
+
+    /// The server cannot process the request due to a client error.
+    BadRequest = 400,
+
     /// The client does not have access rights to the content.
     Forbidden = 403,
 
     /// The server cannot find the requested resource.
     NotFound = 404,
 
+    /// The request method is not supported by the target resource.
+    MethodNotAllowed = 405,
+
+    /// The request URI is longer than the server is willing to interpret.
+    UriTooLong = 414,
+
+    /// None of the requested ranges can be satisfied.
+    RangeNotSatisfiable = 416,
+
+    /// The request was directed at a host that the server does not serve.
+    MisdirectedRequest = 421,
+
+    /// The request's header fields are too large.
+    RequestHeaderFieldsTooLarge = 431,
+
     /// The server has encountered a situation it does not know how to handle.
     InternalServerError = 500,
+
+    /// The HTTP version used in the request is not supported by the server.
+    HttpVersionNotSupported = 505,
 }
 
 impl Status {
@@ -41,139 +97,1067 @@ impl Status {
     pub fn reason(self) -> &'static str {
         match self {
             Self::Ok => "OK",
+            Self::PartialContent => "Partial Content",
+            Self::MovedPermanently => "Moved Permanently",
             Self::Found => "Found",
+            Self::NotModified => "Not Modified",
+            Self::TemporaryRedirect => "Temporary Redirect",
+            Self::PermanentRedirect => "Permanent Redirect",
+            Self::BadRequest => "Bad Request",
             Self::Forbidden => "Forbidden",
             Self::NotFound => "Not Found",
+            Self::MethodNotAllowed => "Method Not Allowed",
+            Self::UriTooLong => "URI Too Long",
+            Self::RangeNotSatisfiable => "Range Not Satisfiable",
+            Self::MisdirectedRequest => "Misdirected Request",
+            Self::RequestHeaderFieldsTooLarge => "Request Header Fields Too Large",
             Self::InternalServerError => "Internal Server Error",
+            Self::HttpVersionNotSupported => "HTTP Version Not Supported",
+        }
+    }
+}
+
+/// An HTTP content coding that files may be precompressed with.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// The Brotli content coding.
+    Brotli,
+
+    /// The gzip content coding.
+    Gzip,
+}
+
+impl Encoding {
+    /// Every `Encoding` in order of preference.
+    pub const ALL: [Self; 2] = [Self::Brotli, Self::Gzip];
+
+    /// Returns the `Encoding`'s name in HTTP header fields.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    /// Returns the file extension of files precompressed with the `Encoding`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Gzip => "gz",
         }
     }
 }
 
+/// The duration to wait for a new [`Request`] on an idle [`Connection`].
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The duration to wait for the rest of a [`Request`]'s head after it starts.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The duration to wait for a response to be written before disconnecting.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The interval between checks for queued [`Connection`]s or a shutdown while
+/// a connection is idle.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
 /// An HTTP server.
-pub struct Server<'a> {
-    /// The `Server`'s configuration data.
-    config: &'a Config,
+pub struct Server {
+    /// The `Server`'s configuration data, shared with each [`Connection`].
+    config: Arc<Config>,
+
+    /// The `Server`'s [`ServerState`], shared with each [`Connection`].
+    state: Arc<ServerState>,
+
+    /// The `Server`'s TCP/IP addresses, one for each [`TcpListener`].
+    addresses: Vec<SocketAddr>,
 
-    /// The [`TcpListener`] listening for [`Request`]s over TCP.
-    listener: TcpListener,
+    /// The receiver for TCP connections accepted by each listener thread.
+    streams: Receiver<io::Result<(TcpStream, SocketAddr)>>,
 
-    /// The `Server`'s TCP/IP address.
-    address: SocketAddr,
+    /// The [`ThreadPool`] for serving [`Connection`]s.
+    pool: ThreadPool<Connection>,
 }
 
-impl<'a> Server<'a> {
-    /// Creates a new `Server` from configuration data. The returned server is
-    /// bound to a TCP port and ready to accept [`Request`]s. The server will be
+impl Server {
+    /// Creates a new `Server` from configuration data and a function to serve
+    /// [`Connection`]s with. The returned server is bound to a TCP port on
+    /// each bind address and ready to accept connections. The server will be
     /// closed when the value is dropped.
-    pub fn try_new(config: &'a Config) -> Result<Self> {
-        let listener =
-            TcpListener::bind((Ipv4Addr::LOCALHOST, config.port())).map_err(Error::ServerOpen)?;
+    pub fn try_new(
+        config: Config,
+        serve: impl Fn(Connection) + Send + Sync + 'static,
+    ) -> Result<Self> {
+        let listeners = Self::try_bind(&config)?;
 
-        let address = listener.local_addr().map_err(Error::ServerAddressQuery)?;
+        let addresses = listeners
+            .iter()
+            .map(TcpListener::local_addr)
+            .collect::<io::Result<Vec<_>>>()
+            .map_err(Error::ServerAddressQuery)?;
+
+        // Each listener blocks its own thread while accepting connections, so
+        // accepted connections are collected through a channel. Each thread
+        // stops after the server is dropped.
+        let (sender, streams) = mpsc::channel();
+
+        for listener in listeners {
+            let sender = sender.clone();
+            thread::spawn(move || while sender.send(listener.accept()).is_ok() {});
+        }
+
+        let state = ServerState {
+            is_shutting_down: AtomicBool::new(false),
+            connection_count: AtomicU64::new(0),
+            queued_connection_count: AtomicUsize::new(0),
+            request_count: AtomicU64::new(0),
+            logger: Logger::try_new(&config)?,
+        };
+
+        let pool = ThreadPool::new(config.threads(), serve);
 
         Ok(Self {
-            config,
-            listener,
-            address,
+            config: Arc::new(config),
+            state: Arc::new(state),
+            addresses,
+            streams,
+            pool,
         })
     }
 
-    /// Accepts a new incoming [`Request`]. The returned request is bound to a
-    /// TCP connection and ready for a response. The connection will be closed
-    /// when the value is dropped. This function will block the calling thread
-    /// until a new TCP connection is established.
-    pub fn try_accept_request(&self) -> Result<Request<'_>> {
-        let (stream, client) = self.listener.accept().map_err(Error::Connect)?;
-        let uri = try_read_request_uri(&stream)?;
+    /// Returns the `Server`'s configuration data.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns the `Server`'s TCP/IP addresses.
+    pub fn addresses(&self) -> &[SocketAddr] {
+        &self.addresses
+    }
 
-        Ok(Request {
-            server: self,
-            stream,
+    /// Returns a [`Url`] for each of the `Server`'s TCP/IP addresses.
+    pub fn urls(&self) -> impl Iterator<Item = Url> {
+        self.addresses.iter().copied().map(Url)
+    }
+
+    /// Returns the TCP port that the `Server` is bound to.
+    pub fn port(&self) -> u16 {
+        self.addresses.first().map_or(0, SocketAddr::port)
+    }
+
+    /// Returns whether the `Server` is running and has not started shutting
+    /// down.
+    pub fn is_running(&self) -> bool {
+        !self.state.is_shutting_down()
+    }
+
+    /// Returns a [`ShutdownHandle`] for shutting down the `Server` from another
+    /// thread.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle {
+            state: Arc::clone(&self.state),
+            addresses: self.addresses.clone(),
+        }
+    }
+
+    /// Returns a [`Url`] for each TCP/IP address that clients other than the
+    /// host machine may connect to. Unspecified addresses are expanded to the
+    /// address of each network interface.
+    pub fn network_urls(&self) -> Vec<Url> {
+        let is_ipv4_bound = self.addresses.iter().any(SocketAddr::is_ipv4);
+        let mut urls = Vec::new();
+
+        for address in &self.addresses {
+            let ip = address.ip();
+
+            if ip.is_loopback() {
+                continue;
+            } else if !ip.is_unspecified() {
+                urls.push(Url(*address));
+                continue;
+            }
+
+            // Unspecified IPv6 addresses may also accept IPv4 connections
+            // unless an IPv4 address is also bound. Link-local IPv6 addresses
+            // are skipped because they cannot be used in URLs without a zone
+            // ID.
+            for interface in if_addrs::get_if_addrs().unwrap_or_default() {
+                let interface_ip = interface.ip();
+
+                if interface.is_loopback()
+                    || (interface_ip.is_ipv6() && (ip.is_ipv4() || interface.is_link_local()))
+                    || (interface_ip.is_ipv4() && ip.is_ipv6() && is_ipv4_bound)
+                {
+                    continue;
+                }
+
+                urls.push(Url(SocketAddr::new(interface_ip, address.port())));
+            }
+        }
+
+        urls
+    }
+
+    /// Accepts a new incoming [`Connection`] from any bind address and
+    /// dispatches it to a worker thread to be served. This function will block
+    /// the calling thread until a new TCP connection is established and a
+    /// worker thread is available.
+    /// Connections accepted while shutting down are closed immediately.
+    pub fn try_dispatch_connection(&self) -> Result<()> {
+        let connection = self.try_accept_connection()?;
+
+        if self.is_running() {
+            self.state.connection_count.fetch_add(1, Ordering::Relaxed);
+
+            self.state
+                .queued_connection_count
+                .fetch_add(1, Ordering::Relaxed);
+
+            self.pool.dispatch(connection);
+        }
+
+        Ok(())
+    }
+
+    /// Consumes the `Server` and waits for its in-flight [`Request`]s to finish
+    /// for up to a timeout. Returns a [`Summary`] of the `Server`'s activity.
+    /// The `Server` should already be shutting down from a [`ShutdownHandle`].
+    pub fn shut_down(self, timeout: Duration) -> Summary {
+        drop(self.streams);
+        let is_complete = self.pool.join_until(Instant::now() + timeout);
+
+        Summary {
+            connection_count: self.state.connection_count.load(Ordering::Relaxed),
+            request_count: self.state.request_count.load(Ordering::Relaxed),
+            is_complete,
+        }
+    }
+
+    /// Binds a [`TcpListener`] to each bind address from configuration data. If
+    /// the port is in use and port fallback is enabled, then successive ports
+    /// are tried before an OS-assigned port.
+    fn try_bind(config: &Config) -> Result<Vec<TcpListener>> {
+        let port = config.port();
+
+        let Some(attempts) = config.port_fallback_attempts() else {
+            return bind_listeners(config.bind_addresses(), port).map_err(|error| {
+                if error.kind() == ErrorKind::AddrInUse {
+                    Error::ServerPortInUse(port)
+                } else {
+                    Error::ServerOpen(error)
+                }
+            });
+        };
+
+        let ports = (0..=attempts)
+            .map_while(|offset| port.checked_add(offset))
+            .chain([0]);
+
+        for port in ports {
+            match bind_listeners(config.bind_addresses(), port) {
+                Ok(listeners) => return Ok(listeners),
+                Err(error) if error.kind() == ErrorKind::AddrInUse => {}
+                Err(error) => return Err(Error::ServerOpen(error)),
+            }
+        }
+
+        Err(Error::ServerPortInUse(port))
+    }
+
+    /// Accepts a new incoming [`Connection`]. The returned connection is bound
+    /// to a TCP stream and ready to read [`Request`]s. The connection will be
+    /// closed when the value is dropped. This function will block the calling
+    /// thread until a new TCP connection is established.
+    fn try_accept_connection(&self) -> Result<Connection> {
+        let (stream, client) = self
+            .streams
+            .recv()
+            .map_err(|_| Error::Connect(ErrorKind::NotConnected.into()))?
+            .map_err(Error::Connect)?;
+
+        // A client that stops reading would otherwise hold a worker thread
+        // indefinitely.
+        stream
+            .set_write_timeout(Some(WRITE_TIMEOUT))
+            .map_err(Error::Connect)?;
+
+        // Response heads and streamed bodies are sent with separate writes, so
+        // Nagle's algorithm would otherwise delay the body.
+        stream.set_nodelay(true).map_err(Error::Connect)?;
+
+        Ok(Connection {
+            config: Arc::clone(&self.config),
+            state: Arc::clone(&self.state),
+            reader: BufReader::new(stream),
             client,
-            uri,
+            received: Instant::now(),
+            is_persistent: true,
+            is_queued: true,
         })
     }
 }
 
-impl Display for Server<'_> {
+impl Display for Server {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, url) in self.urls().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+
+            url.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A URL for connecting to a [`Server`] at a TCP/IP address.
+pub struct Url(SocketAddr);
+
+impl Display for Url {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         const STANDARD_HTTP_PORT: u16 = 80;
         f.write_str("http://")?;
 
-        match self.address.ip() {
+        match self.0.ip() {
             IpAddr::V4(Ipv4Addr::LOCALHOST) => f.write_str("localhost"),
-            ip => ip.fmt(f),
+            IpAddr::V4(ip) => ip.fmt(f),
+            IpAddr::V6(ip) => write!(f, "[{ip}]"),
         }?;
 
-        match self.address.port() {
+        match self.0.port() {
             STANDARD_HTTP_PORT => Ok(()),
             port => write!(f, ":{port}"),
         }
     }
 }
 
-/// An HTTP request received from a client.
-pub struct Request<'a> {
-    /// The [`Server`] that received the `Request`.
-    server: &'a Server<'a>,
+/// State shared between a [`Server`] and its [`Connection`]s.
+struct ServerState {
+    /// Whether the [`Server`] is shutting down.
+    is_shutting_down: AtomicBool,
 
-    /// The [`TcpStream`] for communicating with the client.
-    stream: TcpStream,
+    /// The number of [`Connection`]s accepted by the [`Server`].
+    connection_count: AtomicU64,
+
+    /// The number of [`Connection`]s dispatched by the [`Server`] that are
+    /// waiting for a worker thread.
+    queued_connection_count: AtomicUsize,
+
+    /// The number of [`Request`]s read by the [`Server`].
+    request_count: AtomicU64,
+
+    /// The [`Logger`] for access logging, if access logging is enabled.
+    logger: Option<Logger>,
+}
+
+impl ServerState {
+    /// Returns whether the [`Server`] is shutting down.
+    fn is_shutting_down(&self) -> bool {
+        self.is_shutting_down.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether any [`Connection`]s are waiting for a worker thread.
+    fn has_queued_connections(&self) -> bool {
+        self.queued_connection_count.load(Ordering::Relaxed) > 0
+    }
+}
+
+/// A handle for shutting down a [`Server`] from another thread.
+pub struct ShutdownHandle {
+    /// The [`Server`]'s [`ServerState`].
+    state: Arc<ServerState>,
+
+    /// The [`Server`]'s TCP/IP addresses.
+    addresses: Vec<SocketAddr>,
+}
+
+impl ShutdownHandle {
+    /// Starts shutting down the [`Server`]. The server stops accepting
+    /// [`Connection`]s, and each connection is closed after its current
+    /// [`Request`].
+    pub fn shut_down(&self) {
+        self.state.is_shutting_down.store(true, Ordering::Relaxed);
+
+        // The server may be blocked waiting for a new connection, so a
+        // connection is made to each address to wake it. Unspecified addresses
+        // are reachable through their loopback address.
+        for &address in &self.addresses {
+            let ip = match address.ip() {
+                IpAddr::V4(Ipv4Addr::UNSPECIFIED) => Ipv4Addr::LOCALHOST.into(),
+                IpAddr::V6(Ipv6Addr::UNSPECIFIED) => Ipv6Addr::LOCALHOST.into(),
+                ip => ip,
+            };
+
+            let _ = TcpStream::connect((ip, address.port()));
+        }
+    }
+}
+
+/// A summary of a [`Server`]'s activity after it has shut down.
+pub struct Summary {
+    /// The number of [`Connection`]s accepted by the [`Server`].
+    connection_count: u64,
+
+    /// The number of [`Request`]s read by the [`Server`].
+    request_count: u64,
+
+    /// Whether every in-flight [`Request`] finished before the [`Server`] shut
+    /// down.
+    is_complete: bool,
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Served {} request{} over {} connection{}.",
+            self.request_count,
+            if self.request_count == 1 { "" } else { "s" },
+            self.connection_count,
+            if self.connection_count == 1 { "" } else { "s" },
+        )?;
+
+        if !self.is_complete {
+            f.write_str(" Some requests were cut off.")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A TCP connection with a client that may carry multiple [`Request`]s.
+pub struct Connection {
+    /// The `Connection`'s configuration data.
+    config: Arc<Config>,
+
+    /// The [`ServerState`] of the [`Server`] that accepted the `Connection`.
+    state: Arc<ServerState>,
+
+    /// The buffered [`TcpStream`] for communicating with the client.
+    reader: BufReader<TcpStream>,
 
     /// The client's TCP/IP address.
     client: SocketAddr,
 
-    /// The `Request`'s URI.
+    /// The [`Instant`] that the current [`Request`] started being received.
+    received: Instant,
+
+    /// Whether the `Connection` should remain open after the current
+    /// [`Request`].
+    is_persistent: bool,
+
+    /// Whether the `Connection` is waiting for a worker thread to serve it.
+    is_queued: bool,
+}
+
+impl Connection {
+    /// Reads the next [`Request`] from the `Connection`. Returns [`None`] if
+    /// the `Connection` was closed by either side or timed out while idle.
+    pub fn try_read_request(&mut self) -> Result<Option<Request<'_>>> {
+        if !self.is_persistent || !self.wait_for_request() {
+            self.is_persistent = false;
+            return Ok(None);
+        }
+
+        self.state.request_count.fetch_add(1, Ordering::Relaxed);
+        self.received = Instant::now();
+        let deadline = self.received + REQUEST_TIMEOUT;
+
+        let head = match head::try_read_request_head(&mut self.reader, deadline) {
+            Ok(Some(head)) => head,
+            Ok(None) => {
+                self.is_persistent = false;
+                return Ok(None);
+            }
+            Err(error) => {
+                // The end of a malformed request cannot be found reliably, so
+                // no more requests can be read from the connection.
+                self.is_persistent = false;
+                return Err(error);
+            }
+        };
+
+        // HTTP/1.1 connections are persistent by default, but HTTP/1.0 clients
+        // must opt in with a `Connection: keep-alive` header field. Request
+        // bodies are not read, so any request with a body ends the connection.
+        self.is_persistent = !head.fields.contains_option("Connection", "close")
+            && (head.version == Version::Http11
+                || head.fields.contains_option("Connection", "keep-alive"))
+            && head.fields.get("Transfer-Encoding").is_none()
+            && head
+                .fields
+                .get("Content-Length")
+                .is_none_or(|len| len.trim() == "0");
+
+        if let Err(error) = self.try_check_host(&head) {
+            self.is_persistent = false;
+            return Err(error);
+        }
+
+        Ok(Some(Request::new(self, head)))
+    }
+
+    /// Checks that a [`RequestHead`]'s `Host` header field names the host
+    /// machine, the IP address that the `Connection` was accepted on, or an
+    /// allowed host. This prevents DNS rebinding attacks from reading files
+    /// through a client's browser.
+    fn try_check_host(&self, head: &RequestHead) -> Result<()> {
+        let Some(host) = head.fields.get("Host") else {
+            // HTTP/1.0 clients are not required to send a `Host` header field.
+            return match head.version {
+                Version::Http10 => Ok(()),
+                Version::Http11 => Err(Error::RequestMalformed),
+            };
+        };
+
+        let name = parse_host_name(host).ok_or(Error::RequestMalformed)?;
+
+        let is_allowed = match name.parse::<IpAddr>() {
+            Ok(ip) => {
+                let ip = ip.to_canonical();
+
+                ip.is_loopback()
+                    || self
+                        .reader
+                        .get_ref()
+                        .local_addr()
+                        .is_ok_and(|address| address.ip().to_canonical() == ip)
+            }
+            Err(_) => {
+                let name = name.to_ascii_lowercase();
+                name == "localhost" || name.ends_with(".localhost")
+            }
+        } || self
+            .config
+            .allowed_hosts()
+            .iter()
+            .any(|allowed_host| allowed_host.eq_ignore_ascii_case(name));
+
+        if is_allowed {
+            Ok(())
+        } else {
+            Err(Error::RequestHostNotAllowed)
+        }
+    }
+
+    /// Waits for the start of the next [`Request`]. Returns `false` if the
+    /// `Connection` was closed by the client, timed out while idle, or the
+    /// [`Server`] started shutting down. Idle persistent connections are also
+    /// closed while other connections are waiting for a worker thread.
+    fn wait_for_request(&mut self) -> bool {
+        let deadline = Instant::now() + KEEP_ALIVE_TIMEOUT;
+
+        // The first request is always waited for because the client has only
+        // just been given a worker thread.
+        let is_first_request = self.is_queued;
+
+        if is_first_request {
+            self.is_queued = false;
+
+            self.state
+                .queued_connection_count
+                .fetch_sub(1, Ordering::Relaxed);
+        }
+
+        if self
+            .reader
+            .get_ref()
+            .set_read_timeout(Some(IDLE_POLL_INTERVAL))
+            .is_err()
+        {
+            return false;
+        }
+
+        loop {
+            match self.reader.fill_buf() {
+                Ok(buffer) => break !buffer.is_empty(),
+                Err(error)
+                    if matches!(
+                        error.kind(),
+                        ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted
+                    ) =>
+                {
+                    if self.state.is_shutting_down()
+                        || Instant::now() >= deadline
+                        || (!is_first_request && self.state.has_queued_connections())
+                    {
+                        break false;
+                    }
+                }
+                Err(_) => break false,
+            }
+        }
+    }
+
+    /// Sends a response to the client without a [`Request`]. This is used for
+    /// requests that could not be read.
+    pub fn try_respond(&self, response: &impl Respond) -> Result<()> {
+        self.try_send_response(None, response)
+    }
+
+    /// Sends a response to the client, optionally for a [`Request`].
+    fn try_send_response(&self, request: Option<&Request>, response: &impl Respond) -> Result<()> {
+        let mut status = response.status();
+        let media_type = response.media_type();
+        let mut media_type = media_type.as_ref().map(AsRef::as_ref);
+        let mut fields = String::new();
+
+        let mut entity_tag = response.entity_tag().map(|tag| tag.as_ref().to_string());
+        let last_modified = response.last_modified();
+        let body = response.body();
+
+        // Responses are only compressed on the fly if they are not already
+        // encoded. Responses to HEAD requests are never compressed because the
+        // whole body would be compressed only to find its length.
+        let is_compressible = self.config.is_compressing()
+            && response.encoding().is_none()
+            && media_type.is_some_and(compress::is_compressible_type);
+
+        let compression = request
+            .filter(|request| {
+                is_compressible
+                    && *request.method() != Method::Head
+                    && compress::is_compressible_len(body.len())
+            })
+            .and_then(|request| {
+                Encoding::ALL
+                    .into_iter()
+                    .find(|encoding| request.accepts_encoding(*encoding))
+            });
+
+        if let (Some(encoding), Some(entity_tag)) = (compression, &mut entity_tag) {
+            entity_tag.insert_str(entity_tag.len() - 1, &format!("-{}", encoding.name()));
+        }
+
+        let entity_tag = entity_tag.as_deref();
+
+        if status == Status::MethodNotAllowed {
+            fields.push_str("Allow: GET, HEAD\r\n");
+        }
+
+        if let Some(location) = response.location() {
+            let _ = write!(fields, "Location: {}\r\n", location.as_ref());
+        }
+
+        if let Some(encoding) = response.encoding().or(compression) {
+            let _ = write!(fields, "Content-Encoding: {}\r\n", encoding.name());
+        }
+
+        if response.is_encoding_negotiated() || is_compressible {
+            fields.push_str("Vary: Accept-Encoding\r\n");
+        }
+
+        if let Some(entity_tag) = entity_tag {
+            let _ = write!(fields, "ETag: {entity_tag}\r\n");
+        }
+
+        if let Some(last_modified) = last_modified {
+            let _ = write!(
+                fields,
+                "Last-Modified: {}\r\n",
+                httpdate::fmt_http_date(last_modified)
+            );
+        }
+
+        let is_not_modified = status == Status::Ok
+            && request.is_some_and(|request| request.is_not_modified(entity_tag, last_modified));
+
+        let range = request
+            .filter(|request| request.is_range_current(entity_tag, last_modified))
+            .and_then(|request| request.field("Range"));
+
+        let mut chunks = match body {
+            _ if is_not_modified => {
+                status = Status::NotModified;
+                media_type = None;
+                Vec::new()
+            }
+            body if let Some(encoding) = compression => {
+                let body = match body {
+                    Body::Bytes(bytes) => compress::compress(encoding, bytes.as_ref()),
+                    Body::File(mut file, len) => file
+                        .seek(SeekFrom::Start(0))
+                        .and_then(|_| compress::compress(encoding, file.take(len))),
+                }
+                .map_err(Error::FileRead)?;
+
+                vec![Chunk::Bytes(body.into())]
+            }
+            Body::File(file, len) if status == Status::Ok => {
+                fields.push_str("Accept-Ranges: bytes\r\n");
+
+                match range.and_then(|range| parse_ranges(range, len)) {
+                    None => vec![Chunk::File(file, 0..len)],
+                    Some(ranges) => {
+                        let (range_status, chunks) =
+                            range_chunks(file, len, &ranges, media_type, &mut fields);
+
+                        status = range_status;
+                        media_type = None;
+                        chunks
+                    }
+                }
+            }
+            Body::File(file, len) => vec![Chunk::File(file, 0..len)],
+            Body::Bytes(bytes) => vec![Chunk::Bytes(bytes)],
+        };
+
+        if let Some(media_type) = media_type {
+            let _ = write!(fields, "Content-Type: {media_type}\r\n");
+        }
+
+        let mut packet = format!(
+            "HTTP/1.1 {} {}\r\n\
+            Connection: {}\r\n",
+            status.code(),
+            status.reason(),
+            if self.is_persistent {
+                "keep-alive"
+            } else {
+                "close"
+            }
+        );
+
+        let custom_fields: Vec<_> = self
+            .config
+            .header_fields(request.map(Request::uri))
+            .collect();
+
+        // Custom header fields replace the cross-origin isolation header
+        // fields so that their policies can be relaxed.
+        if self.config.is_cross_origin_isolated() {
+            for (name, value) in [
+                ("Cross-Origin-Opener-Policy", "same-origin"),
+                ("Cross-Origin-Embedder-Policy", "require-corp"),
+            ] {
+                if !custom_fields
+                    .iter()
+                    .any(|field| field.name().eq_ignore_ascii_case(name))
+                {
+                    let _ = write!(packet, "{name}: {value}\r\n");
+                }
+            }
+        }
+
+        for field in custom_fields {
+            let _ = write!(packet, "{}: {}\r\n", field.name(), field.value());
+        }
+
+        packet.push_str(&fields);
+
+        // A not modified response has no body, so its length is not sent.
+        if status != Status::NotModified {
+            let len: u64 = chunks.iter().map(Chunk::len).sum();
+            let _ = write!(packet, "Content-Length: {len}\r\n");
+        }
+
+        packet.push_str("\r\n");
+
+        // A response to a HEAD request has the same header fields as a GET
+        // request, but its body is never read or sent.
+        if request.is_some_and(|request| *request.method() == Method::Head) {
+            chunks.clear();
+        }
+
+        let len = chunks.iter().map(Chunk::len).sum();
+        let result = try_send_chunks(self.reader.get_ref(), packet.into_bytes(), chunks);
+
+        match request {
+            Some(request) => request.log(status, len),
+            None => self.log_unread_request(status, len),
+        }
+
+        result
+    }
+
+    /// Logs a response with a [`Status`] and a body length in bytes to a
+    /// [`Request`] that could not be read. The request's method, target, and
+    /// protocol version are logged as `-`.
+    fn log_unread_request(&self, status: Status, len: u64) {
+        let Some(logger) = &self.state.logger else {
+            return;
+        };
+
+        logger.log(&LogEntry {
+            client: self.client.ip().to_canonical(),
+            method: "-",
+            target: "-",
+            protocol: "-",
+            status: status.code(),
+            bytes: len,
+            duration: self.received.elapsed(),
+            referer: None,
+            user_agent: None,
+        });
+    }
+}
+
+/// An HTTP request received from a client.
+pub struct Request<'a> {
+    /// The [`Connection`] that received the `Request`.
+    connection: &'a Connection,
+
+    /// The `Request`'s [`Method`].
+    method: Method,
+
+    /// The `Request`'s raw target, including any query string.
+    target: String,
+
+    /// The `Request`'s protocol [`Version`].
+    version: Version,
+
+    /// The `Request`'s decoded URI, excluding any query string.
     uri: String,
+
+    /// The `Request`'s [`HeaderFields`].
+    fields: HeaderFields,
+}
+
+impl<'a> Request<'a> {
+    /// Creates a new `Request` from the [`Connection`] that received it and its
+    /// [`RequestHead`].
+    fn new(connection: &'a Connection, head: RequestHead) -> Self {
+        let uri = match head.target.split_once('?') {
+            None => decode_uri(&head.target),
+            Some((path, _)) => decode_uri(path),
+        };
+
+        Self {
+            connection,
+            method: head.method,
+            target: head.target,
+            version: head.version,
+            uri,
+            fields: head.fields,
+        }
+    }
 }
 
 impl Request<'_> {
     /// Returns the configuration data associated with the `Request`.
     pub fn config(&self) -> &Config {
-        self.server.config
+        &self.connection.config
     }
 
-    /// Returns whether the `Request` was sent from the host machine.
-    pub fn is_local(&self) -> bool {
-        self.client.ip().is_loopback()
+    /// Returns whether the `Request` was sent from the host machine or an
+    /// allowed IP network.
+    pub fn is_allowed(&self) -> bool {
+        // Clients of dual-stack listeners may have IPv4-mapped IPv6 addresses.
+        let ip = self.connection.client.ip().to_canonical();
+
+        ip.is_loopback()
+            || self
+                .config()
+                .allowed_networks()
+                .iter()
+                .any(|network| network.contains(&ip))
     }
 
-    /// Returns the `Request`'s URI.
+    /// Returns the `Request`'s [`Method`].
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the `Request`'s raw target, including any query string.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the `Request`'s protocol [`Version`].
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns the `Request`'s decoded URI, excluding any query string.
     pub fn uri(&self) -> &str {
         &self.uri
     }
 
-    /// Consumes the `Request` and sends a response to the client.
-    pub fn try_respond(mut self, response: &impl Respond) -> Result<()> {
-        let status = response.status();
-        let mut packet = format!(
-            "HTTP/1.1 {} {}\r\n\
-            Connection: close\r\n",
-            status.code(),
-            status.reason()
-        );
+    /// Returns the `Request`'s raw query string. Returns [`None`] if the
+    /// `Request` has no query string.
+    pub fn query(&self) -> Option<&str> {
+        self.target.split_once('?').map(|(_, query)| query)
+    }
 
-        if self.config().is_cross_origin_isolated() {
-            packet.push_str(
-                "Cross-Origin-Opener-Policy: same-origin\r\n\
-                Cross-Origin-Embedder-Policy: require-corp\r\n",
-            );
+    /// Returns the value of one of the `Request`'s HTTP header fields from its
+    /// case-insensitive name. Returns [`None`] if the header field is missing.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name)
+    }
+
+    /// Returns whether the `Request` accepts a response with an [`Encoding`].
+    pub fn accepts_encoding(&self, encoding: Encoding) -> bool {
+        let Some(codings) = self.field("Accept-Encoding") else {
+            return false;
+        };
+
+        let mut wildcard_quality = None;
+
+        for coding in codings.split(',') {
+            let mut params = coding.split(';').map(str::trim);
+            let name = params.next().unwrap_or_default();
+
+            let quality = params
+                .find_map(|param| param.strip_prefix("q="))
+                .map_or(Some(1.0), |quality| quality.parse::<f32>().ok());
+
+            if name.eq_ignore_ascii_case(encoding.name()) {
+                return quality.is_some_and(|quality| quality > 0.0);
+            } else if name == "*" {
+                wildcard_quality = quality;
+            }
         }
 
-        if let Some(location) = response.location() {
-            let _ = write!(packet, "Location: {}\r\n", location.as_ref());
+        wildcard_quality.is_some_and(|quality| quality > 0.0)
+    }
+
+    /// Returns whether a response with an entity tag and a last modification
+    /// time would not be modified from the client's cached copy.
+    fn is_not_modified(&self, entity_tag: Option<&str>, last_modified: Option<SystemTime>) -> bool {
+        if let Some(tags) = self.field("If-None-Match") {
+            return entity_tag.is_some_and(|entity_tag| match_entity_tags(tags, entity_tag, false));
         }
 
-        if let Some(media_type) = response.media_type() {
-            let _ = write!(packet, "Content-Type: {}\r\n", media_type.as_ref());
+        match (self.field("If-Modified-Since"), last_modified) {
+            (Some(since), Some(last_modified)) => httpdate::parse_http_date(since)
+                .is_ok_and(|since| truncate_to_secs(last_modified) <= since),
+            _ => false,
         }
+    }
 
-        let body = response.body();
-        let body = body.as_ref();
-        let _ = write!(packet, "Content-Length: {}\r\n\r\n", body.len());
-        let mut packet = packet.into_bytes();
-        packet.extend_from_slice(body);
-        self.stream.write_all(&packet).map_err(Error::ResponseSend)
+    /// Returns whether the `Request`'s range header field should be applied to
+    /// a response with an entity tag and a last modification time.
+    fn is_range_current(
+        &self,
+        entity_tag: Option<&str>,
+        last_modified: Option<SystemTime>,
+    ) -> bool {
+        let Some(condition) = self.field("If-Range") else {
+            return true;
+        };
+
+        if condition.starts_with('"') || condition.starts_with("W/") {
+            entity_tag.is_some_and(|entity_tag| match_entity_tags(condition, entity_tag, true))
+        } else {
+            match (httpdate::parse_http_date(condition), last_modified) {
+                (Ok(date), Some(last_modified)) => truncate_to_secs(last_modified) == date,
+                _ => false,
+            }
+        }
+    }
+
+    /// Consumes the `Request` and sends a response to the client.
+    pub fn try_respond(self, response: &impl Respond) -> Result<()> {
+        self.connection.try_send_response(Some(&self), response)
+    }
+
+    /// Writes an access log entry for a response to the `Request` with a
+    /// [`Status`] and a body length in bytes, if access logging is enabled.
+    fn log(&self, status: Status, len: u64) {
+        let Some(logger) = &self.connection.state.logger else {
+            return;
+        };
+
+        logger.log(&LogEntry {
+            client: self.connection.client.ip().to_canonical(),
+            method: self.method.name(),
+            target: self.target(),
+            protocol: self.version().name(),
+            status: status.code(),
+            bytes: len,
+            duration: self.connection.received.elapsed(),
+            referer: self.field("Referer"),
+            user_agent: self.field("User-Agent"),
+        });
+    }
+
+    /// Consumes the `Request` and opens an [`EventStream`] to the client. The
+    /// `Request`'s [`Connection`] should not be used after the event stream is
+    /// opened.
+    pub fn try_open_event_stream(self) -> Result<EventStream> {
+        /// The duration to wait for an event to be sent before disconnecting.
+        const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+        let mut stream = self
+            .connection
+            .reader
+            .get_ref()
+            .try_clone()
+            .map_err(Error::ResponseSend)?;
+
+        stream
+            .set_write_timeout(Some(WRITE_TIMEOUT))
+            .map_err(Error::ResponseSend)?;
+
+        stream
+            .write_all(
+                b"HTTP/1.1 200 OK\r\n\
+                Connection: close\r\n\
+                Content-Type: text/event-stream\r\n\
+                Cache-Control: no-store\r\n\r\n",
+            )
+            .map_err(Error::ResponseSend)?;
+
+        Ok(EventStream { stream })
+    }
+}
+
+/// A stream of server-sent events to a client.
+pub struct EventStream {
+    /// The [`TcpStream`] for communicating with the client.
+    stream: TcpStream,
+}
+
+impl EventStream {
+    /// Sends an event with a name and no data to the client.
+    pub fn try_send(&mut self, name: &str) -> Result<()> {
+        write!(self.stream, "event: {name}\ndata:\n\n").map_err(Error::ResponseSend)
+    }
+
+    /// Sends a comment to the client to keep the `EventStream` open.
+    pub fn try_keep_alive(&mut self) -> Result<()> {
+        self.stream
+            .write_all(b": keep-alive\n\n")
+            .map_err(Error::ResponseSend)
+    }
+}
+
+/// An HTTP message body.
+pub enum Body<'a> {
+    /// A `Body` stored in memory.
+    Bytes(Cow<'a, [u8]>),
+
+    /// A `Body` streamed from a [`File`] with a known length in bytes.
+    File(&'a File, u64),
+}
+
+impl Body<'_> {
+    /// Returns the `Body`'s length in bytes.
+    fn len(&self) -> u64 {
+        match self {
+            Self::Bytes(bytes) => bytes.len() as u64,
+            Self::File(_, len) => *len,
+        }
+    }
+}
+
+/// A part of an HTTP response to be sent.
+enum Chunk<'a> {
+    /// A `Chunk` stored in memory.
+    Bytes(Cow<'a, [u8]>),
+
+    /// A `Chunk` streamed from a [`Range`] of bytes in a [`File`].
+    File(&'a File, Range<u64>),
+}
+
+impl Chunk<'_> {
+    /// Returns the `Chunk`'s length in bytes.
+    fn len(&self) -> u64 {
+        match self {
+            Self::Bytes(bytes) => bytes.len() as u64,
+            Self::File(_, range) => range.end - range.start,
+        }
     }
 }
 
@@ -197,8 +1181,32 @@ pub trait Respond {
         None::<&str>
     }
 
-    /// Returns the HTTP message body associated with the object.
-    fn body(&self) -> impl AsRef<[u8]>;
+    /// Returns the content [`Encoding`] associated with the object. Returns
+    /// [`None`] if the object is not encoded.
+    fn encoding(&self) -> Option<Encoding> {
+        None
+    }
+
+    /// Returns whether the object's content [`Encoding`] was chosen from the
+    /// request's accepted encodings.
+    fn is_encoding_negotiated(&self) -> bool {
+        false
+    }
+
+    /// Returns the HTTP entity tag associated with the object. Returns [`None`]
+    /// if the object cannot be validated with an entity tag.
+    fn entity_tag(&self) -> Option<impl AsRef<str>> {
+        None::<&str>
+    }
+
+    /// Returns the last modification time associated with the object. Returns
+    /// [`None`] if the object has no known modification time.
+    fn last_modified(&self) -> Option<SystemTime> {
+        None
+    }
+
+    /// Returns the HTTP message [`Body`] associated with the object.
+    fn body(&self) -> Body<'_>;
 }
 
 /// Percent encodes a URI.
@@ -220,37 +1228,245 @@ pub fn decode_uri(uri: &str) -> String {
         .into()
 }
 
-/// Decodes and returns the request URI of an HTTP GET request from a
-/// [`TcpStream`].
-fn try_read_request_uri(stream: &TcpStream) -> Result<String> {
-    const MAX_REQUEST_LEN: u64 = 8192;
-    let mut request = String::new();
-    BufReader::new(stream.take(MAX_REQUEST_LEN))
-        .read_line(&mut request)
-        .map_err(Error::RequestRead)?;
-
-    let mut request = request.split(' ');
-
-    let (Some("GET"), Some(uri), Some(protocol), None) = (
-        request.next(),
-        request.next(),
-        request.next(),
-        request.next(),
-    ) else {
-        return Err(Error::RequestNotHttpGet);
+/// Binds a [`TcpListener`] to each IP address on a shared TCP port. If the port
+/// is `0`, then the port assigned to the first listener is shared.
+fn bind_listeners(ips: &[IpAddr], mut port: u16) -> io::Result<Vec<TcpListener>> {
+    // IPv6 listeners may also accept IPv4 connections, which would conflict
+    // with any IPv4 listeners on the same port.
+    let is_ipv6_only = ips.iter().any(IpAddr::is_ipv4);
+    let mut listeners = Vec::with_capacity(ips.len());
+
+    for &ip in ips {
+        let listener = bind_listener(SocketAddr::new(ip, port), is_ipv6_only)?;
+        port = listener.local_addr()?.port();
+        listeners.push(listener);
+    }
+
+    Ok(listeners)
+}
+
+/// Binds a [`TcpListener`] to a TCP/IP address, optionally preventing IPv6
+/// listeners from accepting IPv4 connections.
+fn bind_listener(address: SocketAddr, is_ipv6_only: bool) -> io::Result<TcpListener> {
+    const BACKLOG: i32 = 128;
+    let socket = Socket::new(
+        Domain::for_address(address),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )?;
+
+    if address.is_ipv6() && is_ipv6_only {
+        socket.set_only_v6(true)?;
+    }
+
+    // Match the standard library, which allows quickly rebinding a port on
+    // Unix after the server is restarted.
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+
+    socket.bind(&address.into())?;
+    socket.listen(BACKLOG)?;
+    Ok(socket.into())
+}
+
+/// Parses the host name or IP address from a `Host` header field, excluding
+/// any port or trailing dot. Returns [`None`] if the header field is malformed.
+fn parse_host_name(host: &str) -> Option<&str> {
+    let (name, port) = match host.strip_prefix('[') {
+        Some(host) => host.split_once(']')?,
+        None => host.split_at(host.find(':').unwrap_or(host.len())),
     };
 
-    if !protocol.starts_with("HTTP/") {
-        return Err(Error::RequestNotHttpGet);
+    let is_port_valid = port.is_empty()
+        || port
+            .strip_prefix(':')
+            .is_some_and(|port| port.bytes().all(|byte| byte.is_ascii_digit()));
+
+    let name = name.strip_suffix('.').unwrap_or(name);
+
+    if name.is_empty() || !is_port_valid {
+        return None;
+    }
+
+    Some(name)
+}
+
+/// Returns whether a list of HTTP entity tags matches an entity tag. Weak
+/// entity tags are only considered matching if the comparison is not strong.
+fn match_entity_tags(tags: &str, entity_tag: &str, is_strong: bool) -> bool {
+    if tags.trim() == "*" {
+        return true;
+    }
+
+    let is_weak = |tag: &str| tag.starts_with("W/");
+
+    if is_strong && is_weak(entity_tag) {
+        return false;
     }
 
-    Ok(decode_uri(trim_query_string(uri)))
+    let entity_tag = entity_tag.trim_start_matches("W/");
+
+    tags.split(',')
+        .map(str::trim)
+        .any(|tag| !(is_strong && is_weak(tag)) && tag.trim_start_matches("W/") == entity_tag)
+}
+
+/// Truncates a [`SystemTime`] to whole seconds to match the precision of HTTP
+/// dates.
+fn truncate_to_secs(time: SystemTime) -> SystemTime {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => UNIX_EPOCH + Duration::from_secs(duration.as_secs()),
+        Err(_) => time,
+    }
 }
 
-/// Returns a URI with any trailing query string removed.
-fn trim_query_string(uri: &str) -> &str {
-    match uri.split_once('?') {
-        None => uri,
-        Some((prefix, _)) => prefix,
+/// Parses an HTTP range header field against a body length in bytes. Returns
+/// [`None`] if the header field should be ignored, or an empty [`Vec`] if none
+/// of the ranges are satisfiable.
+fn parse_ranges(range: &str, len: u64) -> Option<Vec<Range<u64>>> {
+    /// The maximum number of ranges to accept in a single request.
+    const MAX_RANGES: usize = 16;
+
+    let (unit, specs) = range.split_once('=')?;
+
+    if !unit.trim().eq_ignore_ascii_case("bytes") {
+        return None;
     }
+
+    let mut ranges = Vec::new();
+
+    for spec in specs.split(',').map(str::trim) {
+        let (first, last) = spec.split_once('-')?;
+
+        let range = if first.is_empty() {
+            let suffix_len: u64 = last.parse().ok()?;
+            len.saturating_sub(suffix_len)..len
+        } else {
+            let first: u64 = first.parse().ok()?;
+
+            let end = if last.is_empty() {
+                len
+            } else {
+                let last: u64 = last.parse().ok()?;
+
+                if last < first {
+                    return None;
+                }
+
+                last.saturating_add(1).min(len)
+            };
+
+            first..end
+        };
+
+        if !range.is_empty() {
+            ranges.push(range);
+        }
+    }
+
+    (ranges.len() <= MAX_RANGES).then_some(ranges)
+}
+
+/// Returns the response [`Status`] and [`Chunk`]s for sending satisfiable
+/// ranges of a [`File`] with an optional media type. Any header fields
+/// required by the response, including the media type, are appended to a
+/// [`String`].
+fn range_chunks<'a>(
+    file: &'a File,
+    len: u64,
+    ranges: &[Range<u64>],
+    media_type: Option<&str>,
+    fields: &mut String,
+) -> (Status, Vec<Chunk<'a>>) {
+    match ranges {
+        [] => {
+            let _ = write!(fields, "Content-Range: bytes */{len}\r\n");
+            (Status::RangeNotSatisfiable, Vec::new())
+        }
+        [range] => {
+            let _ = write!(
+                fields,
+                "Content-Range: bytes {}-{}/{len}\r\n",
+                range.start,
+                range.end - 1
+            );
+
+            (
+                Status::PartialContent,
+                vec![Chunk::File(file, range.clone())],
+            )
+        }
+        ranges => {
+            let boundary = multipart_boundary();
+
+            let _ = write!(
+                fields,
+                "Content-Type: multipart/byteranges; boundary={boundary}\r\n"
+            );
+
+            let mut chunks = Vec::with_capacity(ranges.len() * 2 + 1);
+
+            for range in ranges {
+                let mut part = format!("\r\n--{boundary}\r\n");
+
+                if let Some(media_type) = media_type {
+                    let _ = write!(part, "Content-Type: {media_type}\r\n");
+                }
+
+                let _ = write!(
+                    part,
+                    "Content-Range: bytes {}-{}/{len}\r\n\r\n",
+                    range.start,
+                    range.end - 1
+                );
+
+                chunks.push(Chunk::Bytes(part.into_bytes().into()));
+                chunks.push(Chunk::File(file, range.clone()));
+            }
+
+            let end = format!("\r\n--{boundary}--\r\n");
+            chunks.push(Chunk::Bytes(end.into_bytes().into()));
+            (Status::PartialContent, chunks)
+        }
+    }
+}
+
+/// Returns a boundary for separating the parts of a multipart body. The
+/// boundary is derived from the current time so that it is unlikely to appear
+/// in the body.
+fn multipart_boundary() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+
+    format!("holo-{nanos:032x}")
+}
+
+/// Sends a response head followed by [`Chunk`]s to a [`TcpStream`]. Adjacent
+/// chunks stored in memory are sent together with the head.
+fn try_send_chunks(mut stream: &TcpStream, mut packet: Vec<u8>, chunks: Vec<Chunk>) -> Result<()> {
+    for chunk in chunks {
+        match chunk {
+            Chunk::Bytes(bytes) => packet.extend_from_slice(&bytes),
+            Chunk::File(mut file, range) => {
+                stream.write_all(&packet).map_err(Error::ResponseSend)?;
+                packet.clear();
+                let len = range.end - range.start;
+
+                file.seek(SeekFrom::Start(range.start))
+                    .map_err(Error::ResponseSend)?;
+
+                // On Linux, copying from a file to a TCP stream uses zero-copy
+                // system calls such as `sendfile` where possible.
+                let copied =
+                    io::copy(&mut file.take(len), &mut stream).map_err(Error::ResponseSend)?;
+
+                if copied < len {
+                    return Err(Error::ResponseSend(ErrorKind::UnexpectedEof.into()));
+                }
+            }
+        }
+    }
+
+    stream.write_all(&packet).map_err(Error::ResponseSend)
 }
@@ -0,0 +1,60 @@
+use std::io::{self, Read, Write};
+
+use flate2::{Compression, write::GzEncoder};
+
+use super::Encoding;
+
+/// The minimum length of a body in bytes for it to be compressed. Smaller
+/// bodies are not worth the overhead of compression.
+const MIN_LEN: u64 = 1024;
+
+/// The maximum length of a body in bytes for it to be compressed. Bodies are
+/// compressed in memory, so larger bodies are sent uncompressed.
+const MAX_LEN: u64 = 64 * 1024 * 1024;
+
+/// The Brotli quality level to compress bodies with. This favors speed over
+/// size because bodies are compressed on every request.
+const BROTLI_QUALITY: u32 = 5;
+
+/// The base 2 logarithm of the Brotli window size to compress bodies with.
+const BROTLI_WINDOW_BITS: u32 = 22;
+
+/// Returns whether a media type is worth compressing.
+pub fn is_compressible_type(media_type: &str) -> bool {
+    let essence = media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    essence.starts_with("text/")
+        || matches!(
+            essence.as_str(),
+            "application/javascript" | "application/json" | "application/wasm" | "image/svg+xml"
+        )
+}
+
+/// Returns whether a body length in bytes is within the range to compress.
+pub fn is_compressible_len(len: u64) -> bool {
+    (MIN_LEN..=MAX_LEN).contains(&len)
+}
+
+/// Reads and compresses a body with an [`Encoding`].
+pub fn compress(encoding: Encoding, mut body: impl Read) -> io::Result<Vec<u8>> {
+    match encoding {
+        Encoding::Brotli => {
+            let mut writer =
+                brotli::CompressorWriter::new(Vec::new(), 4096, BROTLI_QUALITY, BROTLI_WINDOW_BITS);
+
+            io::copy(&mut body, &mut writer)?;
+            writer.flush()?;
+            Ok(writer.into_inner())
+        }
+        Encoding::Gzip => {
+            let mut writer = GzEncoder::new(Vec::new(), Compression::default());
+            io::copy(&mut body, &mut writer)?;
+            writer.finish()
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    io::{BufRead, BufReader, ErrorKind},
+    net::TcpStream,
+    time::Instant,
+};
+
+use crate::error::{Error, Result};
+
+/// The maximum length of a request line in bytes.
+const MAX_REQUEST_LINE_LEN: usize = 8192;
+
+/// The maximum length of a header field line in bytes.
+const MAX_FIELD_LEN: usize = 8192;
+
+/// The maximum number of header fields in a request.
+const MAX_FIELD_COUNT: usize = 100;
+
+/// An HTTP request method.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Method {
+    /// A request for a representation of a resource.
+    Get,
+
+    /// A request for the header fields of a representation of a resource.
+    Head,
+
+    /// A request with any other method.
+    Other(String),
+}
+
+impl Method {
+    /// Returns the `Method`'s name.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Get => "GET",
+            Self::Head => "HEAD",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for Method {
+    fn from(name: &str) -> Self {
+        match name {
+            "GET" => Self::Get,
+            "HEAD" => Self::Head,
+            name => Self::Other(name.into()),
+        }
+    }
+}
+
+impl Display for Method {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A supported HTTP protocol version.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    /// HTTP/1.0.
+    Http10,
+
+    /// HTTP/1.1.
+    Http11,
+}
+
+impl Version {
+    /// Returns the `Version`'s name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Http10 => "HTTP/1.0",
+            Self::Http11 => "HTTP/1.1",
+        }
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A collection of HTTP header fields with case-insensitive names.
+#[derive(Default)]
+pub struct HeaderFields {
+    /// The header field values keyed by lowercase name.
+    values: HashMap<String, String>,
+}
+
+impl HeaderFields {
+    /// Returns the value of a header field from its case-insensitive name.
+    /// Returns [`None`] if the header field is missing.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Returns whether a comma-separated header field contains an option with
+    /// a case-insensitive name.
+    pub fn contains_option(&self, name: &str, option: &str) -> bool {
+        self.get(name).is_some_and(|value| {
+            value
+                .split(',')
+                .any(|value| value.trim().eq_ignore_ascii_case(option))
+        })
+    }
+
+    /// Inserts a header field. Repeated header fields are combined into a
+    /// comma-separated list.
+    fn insert(&mut self, name: &str, value: &str) {
+        self.values
+            .entry(name.to_ascii_lowercase())
+            .and_modify(|values| {
+                values.push_str(", ");
+                values.push_str(value);
+            })
+            .or_insert_with(|| value.into());
+    }
+}
+
+/// The head of an HTTP request.
+pub struct RequestHead {
+    /// The request [`Method`].
+    pub method: Method,
+
+    /// The raw request target, including any query string.
+    pub target: String,
+
+    /// The protocol [`Version`].
+    pub version: Version,
+
+    /// The [`HeaderFields`].
+    pub fields: HeaderFields,
+}
+
+/// Reads a [`RequestHead`] from a buffered TCP stream before a deadline.
+/// Returns [`None`] if the stream was closed before a request was started.
+pub fn try_read_request_head(
+    reader: &mut BufReader<TcpStream>,
+    deadline: Instant,
+) -> Result<Option<RequestHead>> {
+    let line = match try_read_line(reader, MAX_REQUEST_LINE_LEN, deadline) {
+        Ok(Some(line)) => line,
+        Ok(None) => return Ok(None),
+        Err(Error::RequestHeaderTooLarge) => return Err(Error::RequestUriTooLong),
+        Err(error) => return Err(error),
+    };
+
+    let mut parts = line.split(' ');
+
+    let (Some(method), Some(target), Some(protocol), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(Error::RequestMalformed);
+    };
+
+    if method.is_empty() || !is_origin_form(target) {
+        return Err(Error::RequestMalformed);
+    }
+
+    let version = match protocol {
+        "HTTP/1.0" => Version::Http10,
+        "HTTP/1.1" => Version::Http11,
+        protocol if protocol.starts_with("HTTP/") => {
+            return Err(Error::RequestVersionNotSupported);
+        }
+        _ => return Err(Error::RequestMalformed),
+    };
+
+    let mut fields = HeaderFields::default();
+    let mut field_count = 0;
+
+    loop {
+        let line =
+            try_read_line(reader, MAX_FIELD_LEN, deadline)?.ok_or(Error::RequestMalformed)?;
+
+        if line.is_empty() {
+            break;
+        }
+
+        field_count += 1;
+
+        if field_count > MAX_FIELD_COUNT {
+            return Err(Error::RequestHeaderTooLarge);
+        }
+
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(Error::RequestMalformed);
+        };
+
+        // Field names may not be empty or surrounded by whitespace, which also
+        // rejects obsolete line folding.
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(Error::RequestMalformed);
+        }
+
+        fields.insert(name, value.trim());
+    }
+
+    Ok(Some(RequestHead {
+        method: method.into(),
+        target: target.into(),
+        version,
+        fields,
+    }))
+}
+
+/// Reads a line without its line terminator from a buffered TCP stream with a
+/// maximum length in bytes before a deadline. Returns [`None`] if the stream
+/// was closed before the line was started.
+fn try_read_line(
+    reader: &mut BufReader<TcpStream>,
+    max_len: usize,
+    deadline: Instant,
+) -> Result<Option<String>> {
+    let mut line = Vec::new();
+
+    // The read timeout is shrunk before each read so that clients cannot avoid
+    // the deadline by sending the head slowly.
+    while line.len() <= max_len {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+
+        if timeout.is_zero() {
+            return Err(Error::RequestRead(ErrorKind::TimedOut.into()));
+        }
+
+        reader
+            .get_ref()
+            .set_read_timeout(Some(timeout))
+            .map_err(Error::RequestRead)?;
+
+        let buffer = match reader.fill_buf() {
+            Ok(buffer) => buffer,
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted
+                ) =>
+            {
+                continue;
+            }
+            Err(error) => return Err(Error::RequestRead(error)),
+        };
+
+        let buffer = &buffer[..buffer.len().min(max_len + 1 - line.len())];
+
+        let (len, is_done) = match buffer.iter().position(|&byte| byte == b'\n') {
+            Some(index) => (index + 1, true),
+            None => (buffer.len(), buffer.is_empty()),
+        };
+
+        line.extend_from_slice(&buffer[..len]);
+        reader.consume(len);
+
+        if is_done {
+            break;
+        }
+    }
+
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    if line.pop() != Some(b'\n') {
+        return Err(if line.len() >= max_len {
+            Error::RequestHeaderTooLarge
+        } else {
+            Error::RequestMalformed
+        });
+    }
+
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|_| Error::RequestMalformed)
+}
+
+/// Returns whether a request target is in origin form, i.e. an absolute path
+/// followed by an optional query string. Control characters are rejected so
+/// that the query string can be sent back in a `Location` header field.
+fn is_origin_form(target: &str) -> bool {
+    target.starts_with('/') && !target.contains(|char: char| char == '#' || char.is_control())
+}
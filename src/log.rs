@@ -0,0 +1,242 @@
+use std::{
+    env,
+    fmt::Write as _,
+    fs::{File, OpenOptions},
+    io::{self, IsTerminal, Write},
+    net::IpAddr,
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::json;
+
+use crate::{
+    config::{Config, LogFormat},
+    error::{Error, Result},
+};
+
+/// An entry in the access log describing a response to a request.
+pub struct LogEntry<'a> {
+    /// The client's IP address.
+    pub client: IpAddr,
+
+    /// The request's method name.
+    pub method: &'a str,
+
+    /// The request's raw target, including any query string.
+    pub target: &'a str,
+
+    /// The request's HTTP protocol version.
+    pub protocol: &'a str,
+
+    /// The response's status code.
+    pub status: u16,
+
+    /// The number of body bytes sent in the response.
+    pub bytes: u64,
+
+    /// The duration from receiving the request to sending the response.
+    pub duration: Duration,
+
+    /// The request's `Referer` header field, if any.
+    pub referer: Option<&'a str>,
+
+    /// The request's `User-Agent` header field, if any.
+    pub user_agent: Option<&'a str>,
+}
+
+/// A writer for access log entries.
+pub struct Logger {
+    /// The [`LogFormat`] to write entries in.
+    format: LogFormat,
+
+    /// Whether to color entries with ANSI escape codes.
+    is_colored: bool,
+
+    /// The output to write entries to.
+    output: Mutex<Output>,
+}
+
+impl Logger {
+    /// Creates a new `Logger` from configuration data. Returns [`None`] if
+    /// access logging is disabled.
+    pub fn try_new(config: &Config) -> Result<Option<Self>> {
+        let Some(format) = config.log_format() else {
+            return Ok(None);
+        };
+
+        let (output, is_terminal) = match config.log_file() {
+            None => (Output::Stdout(io::stdout()), io::stdout().is_terminal()),
+            Some(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(Error::LogFileOpen)?;
+
+                (Output::File(file), false)
+            }
+        };
+
+        // See https://no-color.org.
+        let is_colored = is_terminal && env::var_os("NO_COLOR").is_none_or(|var| var.is_empty());
+
+        Ok(Some(Self {
+            format,
+            is_colored,
+            output: Mutex::new(output),
+        }))
+    }
+
+    /// Writes a [`LogEntry`]. Any I/O errors encountered while writing will be
+    /// ignored.
+    pub fn log(&self, entry: &LogEntry) {
+        let mut line = match self.format {
+            LogFormat::Combined => combined_line(entry),
+            LogFormat::Dev => dev_line(entry, self.is_colored),
+            LogFormat::Json => json_line(entry),
+        };
+
+        line.push('\n');
+
+        let mut output = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let _ = match &mut *output {
+            Output::Stdout(stdout) => stdout.write_all(line.as_bytes()),
+            Output::File(file) => file.write_all(line.as_bytes()),
+        };
+    }
+}
+
+/// An output for access log entries.
+enum Output {
+    /// The standard output stream.
+    Stdout(io::Stdout),
+
+    /// A log file.
+    File(File),
+}
+
+/// Returns a [`LogEntry`] in the Combined Log Format, followed by the duration
+/// in microseconds.
+fn combined_line(entry: &LogEntry) -> String {
+    let (year, month, day, hour, minute, second) = utc_date_time(SystemTime::now());
+
+    format!(
+        "{} - - [{day:02}/{}/{year}:{hour:02}:{minute:02}:{second:02} +0000] \"{} {} {}\" {} {} \
+        \"{}\" \"{}\" {}",
+        entry.client,
+        MONTH_NAMES[month as usize - 1],
+        entry.method,
+        escape_quotes(entry.target),
+        entry.protocol,
+        entry.status,
+        match entry.bytes {
+            0 => "-".into(),
+            bytes => bytes.to_string(),
+        },
+        escape_quotes(entry.referer.unwrap_or("-")),
+        escape_quotes(entry.user_agent.unwrap_or("-")),
+        entry.duration.as_micros(),
+    )
+}
+
+/// Returns a compact [`LogEntry`] for developers, optionally colored by status
+/// code.
+fn dev_line(entry: &LogEntry, is_colored: bool) -> String {
+    let mut line = format!("{} {} {} ", entry.client, entry.method, entry.target);
+
+    if is_colored {
+        let color = match entry.status {
+            500.. => 31,
+            400.. => 33,
+            300.. => 36,
+            _ => 32,
+        };
+
+        let _ = write!(line, "\x1b[{color}m{}\x1b[0m \x1b[90m", entry.status);
+    } else {
+        let _ = write!(line, "{} ", entry.status);
+    }
+
+    let _ = write!(
+        line,
+        "{} B {:.1} ms",
+        entry.bytes,
+        entry.duration.as_secs_f64() * 1000.0
+    );
+
+    if is_colored {
+        line.push_str("\x1b[0m");
+    }
+
+    line
+}
+
+/// Returns a [`LogEntry`] as a line of JSON.
+fn json_line(entry: &LogEntry) -> String {
+    let (year, month, day, hour, minute, second) = utc_date_time(SystemTime::now());
+
+    json!({
+        "time": format!("{year}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z"),
+        "client": entry.client,
+        "method": entry.method,
+        "target": entry.target,
+        "protocol": entry.protocol,
+        "status": entry.status,
+        "bytes": entry.bytes,
+        "duration_ms": entry.duration.as_secs_f64() * 1000.0,
+        "referer": entry.referer,
+        "user_agent": entry.user_agent,
+    })
+    .to_string()
+}
+
+/// The abbreviated month names used by the Combined Log Format.
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Returns the UTC year, month, day, hour, minute, and second of a
+/// [`SystemTime`]. Months and days are counted from 1.
+fn utc_date_time(time: SystemTime) -> (u64, u64, u64, u64, u64, u64) {
+    /// The number of seconds in a day.
+    const DAY_SECS: u64 = 86_400;
+
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+
+    let time_secs = secs % DAY_SECS;
+
+    // Converts days since the Unix epoch to a civil date using Howard Hinnant's
+    // algorithm. Years are counted from March so that leap days come last.
+    let days = secs / DAY_SECS + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    (
+        year,
+        month,
+        day,
+        time_secs / 3600,
+        time_secs / 60 % 60,
+        time_secs % 60,
+    )
+}
+
+/// Escapes double quotes and backslashes in a quoted log field.
+fn escape_quotes(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
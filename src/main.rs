@@ -1,12 +1,31 @@
 mod config;
 mod error;
+mod header;
 mod http;
+mod log;
 mod page;
+mod pool;
+mod redirect;
 mod router;
+mod watch;
 
-use std::process::ExitCode;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{self, ExitCode},
+    time::Duration,
+};
 
-use crate::{config::Config, error::Result, http::Server};
+use serde_json::json;
+
+use crate::{
+    config::{AddressFormat, Config},
+    error::{Error, Result},
+    http::{Connection, Server},
+    page::Page,
+    watch::Watcher,
+};
 
 /// Runs Holo and returns an [`ExitCode`].
 fn main() -> ExitCode {
@@ -19,26 +38,176 @@ fn main() -> ExitCode {
     }
 }
 
-/// Runs Holo.
+/// The duration to wait for in-flight requests when shutting down.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runs Holo until it is shut down.
 fn try_run() -> Result<()> {
     let config = Config::try_new()?;
-    let server = Server::try_new(&config)?;
-    println!("Serving files at '{server}'...");
-    println!("Use 'Ctrl+C' to exit.");
 
-    loop {
-        let request = match server.try_accept_request() {
-            Ok(request) => request,
-            Err(error) => {
-                error.print();
-                continue;
-            }
-        };
+    let watcher = if config.is_watching() {
+        // The log file and ready file may be in the root directory, but writing
+        // them must not reload pages.
+        let ignored_paths: Vec<_> = [
+            config.log_file().map(Path::to_path_buf),
+            config.ready_file().map(Path::to_path_buf),
+            config.ready_file().map(ready_temp_path),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        Some(Watcher::try_new(config.root(), &ignored_paths)?)
+    } else {
+        None
+    };
+
+    let server = Server::try_new(config, move |connection| {
+        serve_connection(connection, watcher.as_ref());
+    })?;
+
+    let shutdown_handle = server.shutdown_handle();
+
+    ctrlc::set_handler(move || {
+        // Printing may fail if stdout was closed, which must not stop the
+        // server from shutting down.
+        let _ = writeln!(io::stdout(), "Shutting down...");
+        shutdown_handle.shut_down();
+    })
+    .map_err(Error::SignalHandler)?;
+
+    let config = server.config();
+    let desired_port = config.port();
+
+    if desired_port != 0 && server.port() != desired_port {
+        eprintln!(
+            "warning: port {desired_port} is in use, using port {} instead",
+            server.port()
+        );
+    }
+
+    match config.address_format() {
+        AddressFormat::Text => println!("Serving files at {server}..."),
+        AddressFormat::Json => println!("{}", ready_json(&server)),
+    }
+
+    if !config.allowed_networks().is_empty() {
+        print_network_warning(&server);
+    }
+
+    let ready_file = config.ready_file().map(Path::to_path_buf);
 
-        let page = router::find_page(&request);
+    if let Some(path) = &ready_file {
+        try_write_ready_file(path, &ready_json(&server))?;
+    }
+
+    if config.address_format() == AddressFormat::Text {
+        println!("Use 'Ctrl+C' to exit.");
+    }
 
-        if let Err(error) = request.try_respond(&page) {
+    while server.is_running() {
+        if let Err(error) = server.try_dispatch_connection() {
             error.print();
         }
     }
+
+    let summary = server.shut_down(SHUTDOWN_TIMEOUT);
+    let _ = writeln!(io::stdout(), "{summary}");
+
+    // A ready file left behind would wrongly report that the server is ready.
+    if let Some(path) = ready_file {
+        let _ = fs::remove_file(path);
+    }
+
+    Ok(())
+}
+
+/// Returns a line of JSON describing a listening [`Server`] for scripts and
+/// test runners.
+fn ready_json(server: &Server) -> String {
+    json!({
+        "urls": server.urls().map(|url| url.to_string()).collect::<Vec<_>>(),
+        "addresses": server.addresses(),
+        "port": server.port(),
+        "pid": process::id(),
+        "root": server.config().root().to_string_lossy(),
+    })
+    .to_string()
+}
+
+/// Writes JSON to a ready file. The file is written to a temporary path first
+/// so that it is never read while incomplete.
+fn try_write_ready_file(path: &Path, json: &str) -> Result<()> {
+    let temp_path = ready_temp_path(path);
+
+    fs::write(&temp_path, json)
+        .and_then(|()| fs::rename(&temp_path, path))
+        .map_err(Error::ReadyFileWrite)
+}
+
+/// Returns the temporary path that a ready file is written to before it is
+/// renamed.
+fn ready_temp_path(path: &Path) -> PathBuf {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    temp_path.into()
+}
+
+/// Prints a warning listing the URLs that a [`Server`] exposes files to
+/// allowed clients on the network at.
+fn print_network_warning(server: &Server) {
+    let urls = server.network_urls();
+
+    if urls.is_empty() {
+        eprintln!(
+            "warning: allowed clients cannot connect to loopback addresses, use '--bind' to \
+            listen on a network interface"
+        );
+
+        return;
+    }
+
+    eprintln!("warning: files are exposed to allowed clients at:");
+
+    for url in urls {
+        eprintln!("  {url}");
+    }
+}
+
+/// Serves a [`Connection`] on a worker thread with an optional [`Watcher`] and
+/// prints any errors.
+fn serve_connection(mut connection: Connection, watcher: Option<&Watcher>) {
+    if let Err(error) = try_serve_connection(&mut connection, watcher) {
+        error.print();
+    }
+}
+
+/// Serves every [`Request`][http::Request] received over a
+/// [`Connection`] until it is closed. Requests that cannot be read are served
+/// an error [`Page`] if the error was caused by the client. Live reload
+/// requests are subscribed to an optional [`Watcher`].
+fn try_serve_connection(connection: &mut Connection, watcher: Option<&Watcher>) -> Result<()> {
+    loop {
+        match connection.try_read_request() {
+            Ok(Some(request)) => {
+                if let Some(watcher) = watcher
+                    && router::is_live_reload_request(&request)
+                {
+                    watcher.subscribe(request.try_open_event_stream()?);
+                    return Ok(());
+                }
+
+                let page = router::find_page(&request);
+                request.try_respond(&page)?;
+            }
+            Ok(None) => return Ok(()),
+            Err(error) => {
+                if let Some(status) = error.status() {
+                    connection.try_respond(&Page::Error(status))?;
+                }
+
+                return Err(error);
+            }
+        }
+    }
 }
@@ -1,56 +1,139 @@
 use std::{
-    borrow::Cow,
     fmt::{self, Display, Formatter, Write},
+    fs::{File, Metadata},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
-use crate::http::{self, Respond, Status};
+use crate::http::{self, Body, Encoding, Respond, Status};
 
 /// A page that can be sent as an HTTP response.
 pub enum Page {
-    /// A file `Page` with an optional media type and contents.
-    File(Option<&'static str>, Vec<u8>),
+    /// A file `Page` with an [`Asset`].
+    File(Asset),
 
     /// An index `Page` of a URI with directory and file names.
     Index(String, Vec<String>),
 
-    /// A redirection `Page` to an encoded URI.
-    Redirect(String),
+    /// A redirection `Page` with an HTTP response [`Status`] code to an encoded
+    /// URI.
+    Redirect(Status, String),
 
     /// An error `Page` for an HTTP response [`Status`] code.
     Error(Status),
+
+    /// A custom error `Page` for an HTTP response [`Status`] code with an
+    /// [`Asset`].
+    CustomError(Status, Asset),
+}
+
+/// A file to be sent in a [`Page`].
+pub struct Asset {
+    /// The `Asset`'s media type, if known.
+    pub media_type: Option<&'static str>,
+
+    /// The `Asset`'s content [`Encoding`] if it is precompressed.
+    pub encoding: Option<Encoding>,
+
+    /// Whether the `Asset` has precompressed variants to choose from.
+    pub is_encoding_negotiated: bool,
+
+    /// The `Asset`'s open [`File`].
+    pub file: File,
+
+    /// The `Asset`'s [`Metadata`].
+    pub metadata: Metadata,
+
+    /// The `Asset`'s contents if they were modified in memory instead of being
+    /// streamed from its [`File`].
+    pub contents: Option<Vec<u8>>,
 }
 
 impl Respond for Page {
     fn status(&self) -> Status {
         match self {
-            Self::File(_, _) | Self::Index(_, _) => Status::Ok,
-            Self::Redirect(_) => Status::Found,
-            Self::Error(status) => *status,
+            Self::File(_) | Self::Index(_, _) => Status::Ok,
+            Self::Redirect(status, _) | Self::Error(status) | Self::CustomError(status, _) => {
+                *status
+            }
         }
     }
 
     fn location(&self) -> Option<impl AsRef<str>> {
         match self {
-            Self::File(_, _) | Self::Index(_, _) | Self::Error(_) => None,
-            Self::Redirect(uri) => Some(uri),
+            Self::File(_) | Self::Index(_, _) | Self::Error(_) | Self::CustomError(_, _) => None,
+            Self::Redirect(_, uri) => Some(uri),
         }
     }
 
     fn media_type(&self) -> Option<impl AsRef<str>> {
         match self {
-            Self::File(media_type, _) => *media_type,
-            Self::Index(_, _) | Self::Redirect(_) | Self::Error(_) => {
+            Self::File(asset) | Self::CustomError(_, asset) => asset.media_type,
+            Self::Index(_, _) | Self::Redirect(_, _) | Self::Error(_) => {
                 Some("text/html; charset=utf-8")
             }
         }
     }
 
-    fn body(&self) -> impl AsRef<[u8]> {
+    fn encoding(&self) -> Option<Encoding> {
+        match self {
+            Self::File(asset) | Self::CustomError(_, asset) => asset.encoding,
+            Self::Index(_, _) | Self::Redirect(_, _) | Self::Error(_) => None,
+        }
+    }
+
+    fn is_encoding_negotiated(&self) -> bool {
+        match self {
+            Self::File(asset) | Self::CustomError(_, asset) => asset.is_encoding_negotiated,
+            Self::Index(_, _) | Self::Redirect(_, _) | Self::Error(_) => false,
+        }
+    }
+
+    fn entity_tag(&self) -> Option<impl AsRef<str>> {
+        match self {
+            Self::File(asset) => {
+                // The entity tag is derived from the file's size and
+                // modification time, which change whenever it is rebuilt.
+                let metadata = &asset.metadata;
+                let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+                let mut entity_tag = format!("\"{:x}-{:x}", metadata.len(), modified.as_nanos());
+
+                if let Some(encoding) = asset.encoding {
+                    let _ = write!(entity_tag, "-{}", encoding.name());
+                }
+
+                if asset.contents.is_some() {
+                    entity_tag.push_str("-live");
+                }
+
+                entity_tag.push('"');
+                Some(entity_tag)
+            }
+            // Custom error pages are not validated so that an error is never
+            // replaced with a not modified response.
+            Self::Index(_, _) | Self::Redirect(_, _) | Self::Error(_) | Self::CustomError(_, _) => {
+                None
+            }
+        }
+    }
+
+    fn last_modified(&self) -> Option<SystemTime> {
+        match self {
+            Self::File(asset) => asset.metadata.modified().ok(),
+            Self::Index(_, _) | Self::Redirect(_, _) | Self::Error(_) | Self::CustomError(_, _) => {
+                None
+            }
+        }
+    }
+
+    fn body(&self) -> Body<'_> {
         match self {
-            Self::File(_, contents) => Cow::from(contents),
-            Self::Index(uri, names) => render_index(uri, names).into(),
-            Self::Redirect(uri) => render_redirect(uri).into(),
-            Self::Error(status) => render_error(*status).into(),
+            Self::File(asset) | Self::CustomError(_, asset) => match &asset.contents {
+                None => Body::File(&asset.file, asset.metadata.len()),
+                Some(contents) => Body::Bytes(contents.into()),
+            },
+            Self::Index(uri, names) => Body::Bytes(render_index(uri, names).into()),
+            Self::Redirect(_, uri) => Body::Bytes(render_redirect(uri).into()),
+            Self::Error(status) => Body::Bytes(render_error(*status).into()),
         }
     }
 }
@@ -104,7 +187,8 @@ fn render_redirect(uri: &str) -> Vec<u8> {
     let title = "Redirecting";
 
     let content = format!(
-        "<p>You are being redirected to <a href=\"{uri}\">{}</a>.</p>",
+        "<p>You are being redirected to <a href=\"{}\">{}</a>.</p>",
+        HtmlText(uri),
         HtmlText(&http::decode_uri(uri))
     );
 
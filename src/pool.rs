@@ -0,0 +1,92 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        Arc, Mutex, PoisonError,
+        mpsc::{self, Receiver, SyncSender},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// A bounded pool of worker threads that process jobs of type `T`.
+pub struct ThreadPool<T> {
+    /// The [`SyncSender`] for dispatching jobs to the worker threads. This is
+    /// only [`None`] while the `ThreadPool` is being dropped.
+    sender: Option<SyncSender<T>>,
+
+    /// The [`JoinHandle`]s of the worker threads.
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> ThreadPool<T> {
+    /// Creates a new `ThreadPool` from a number of worker threads and a
+    /// function to process jobs with. Dispatching a job will block the calling
+    /// thread while every worker thread is busy and the queue is full.
+    pub fn new(size: NonZeroUsize, process: impl Fn(T) + Send + Sync + 'static) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(size.get());
+        let receiver = Arc::new(Mutex::new(receiver));
+        let process = Arc::new(process);
+
+        let workers = (0..size.get())
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                let process = Arc::clone(&process);
+                thread::spawn(move || run_worker(&receiver, &*process))
+            })
+            .collect();
+
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    /// Dispatches a job to the `ThreadPool`.
+    pub fn dispatch(&self, job: T) {
+        if let Some(sender) = &self.sender {
+            // Sending only fails if every worker thread has stopped, in which
+            // case the job is dropped.
+            let _ = sender.send(job);
+        }
+    }
+
+    /// Consumes the `ThreadPool` and waits until every worker thread has
+    /// finished its queued jobs or a deadline is reached. Returns whether every
+    /// worker thread finished in time. Unfinished worker threads are detached.
+    pub fn join_until(mut self, deadline: Instant) -> bool {
+        /// The interval between checks for finished worker threads.
+        const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+        // Dropping the sender stops each worker thread after its queued jobs
+        // are finished.
+        self.sender = None;
+
+        loop {
+            if self.workers.iter().all(JoinHandle::is_finished) {
+                return true;
+            }
+
+            if Instant::now() >= deadline {
+                return false;
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Runs a worker thread that processes jobs from a shared [`Receiver`] until
+/// it is disconnected.
+fn run_worker<T>(receiver: &Mutex<Receiver<T>>, process: &impl Fn(T)) {
+    loop {
+        let job = receiver
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .recv();
+
+        match job {
+            Ok(job) => process(job),
+            Err(_) => break,
+        }
+    }
+}
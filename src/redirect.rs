@@ -0,0 +1,224 @@
+use std::{cmp::Reverse, fs, io::ErrorKind, path::Path, result};
+
+use serde::Deserialize;
+
+use crate::{
+    error::{Error, Result},
+    http::{self, Status},
+};
+
+/// The name of the redirect rules file that is loaded from the root directory.
+pub const RULES_FILE_NAME: &str = "_redirects";
+
+/// A redirect or rewrite rule.
+#[derive(Deserialize)]
+#[serde(try_from = "RuleTable")]
+pub struct Rule {
+    /// The source pattern's path segments.
+    from: Vec<Segment>,
+
+    /// Whether the source pattern ends with a splat that matches any remaining
+    /// path segments.
+    is_splat: bool,
+
+    /// The encoded target URI or URL, which may contain placeholders.
+    to: String,
+
+    /// The response [`Status`]. [`Status::Ok`] and [`Status::NotFound`] rewrite
+    /// the request instead of redirecting it.
+    status: Status,
+
+    /// Whether the `Rule` applies even if a file exists at the request's URI.
+    is_forced: bool,
+}
+
+impl Rule {
+    /// Creates a new `Rule` from a source pattern, a target, a status code, and
+    /// whether the rule is forced. Returns a message describing why the rule is
+    /// invalid on failure.
+    fn try_new(from: &str, to: &str, status: u16, is_forced: bool) -> result::Result<Self, String> {
+        let status = match status {
+            200 => Status::Ok,
+            301 => Status::MovedPermanently,
+            302 => Status::Found,
+            307 => Status::TemporaryRedirect,
+            308 => Status::PermanentRedirect,
+            404 => Status::NotFound,
+            status => return Err(format!("status code {status} is not supported")),
+        };
+
+        if !from.starts_with('/') {
+            return Err(format!("source '{from}' is not an absolute path"));
+        }
+
+        let (from, is_splat) = match from.strip_suffix('*') {
+            None => (from, false),
+            Some(from) if from.ends_with('/') => (from, true),
+            Some(_) => return Err(format!("splat in source '{from}*' is not a path segment")),
+        };
+
+        let from = path_segments(from)
+            .map(|segment| match segment.strip_prefix(':') {
+                None => Segment::Exact(segment.into()),
+                Some(name) => Segment::Placeholder(name.into()),
+            })
+            .collect();
+
+        // Targets are sent in response heads, so they may not end a line.
+        if to.contains(|char: char| char.is_whitespace() || char.is_control()) {
+            return Err(format!("target '{to}' contains whitespace"));
+        }
+
+        // Rewrites to other hosts would require a proxy.
+        if matches!(status, Status::Ok | Status::NotFound) && !to.starts_with('/') {
+            return Err(format!("rewrite target '{to}' is not an absolute path"));
+        }
+
+        Ok(Self {
+            from,
+            is_splat,
+            to: to.into(),
+            status,
+            is_forced,
+        })
+    }
+
+    /// Returns the `Rule`'s response [`Status`].
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Returns whether the `Rule` applies even if a file exists at the
+    /// request's URI.
+    pub fn is_forced(&self) -> bool {
+        self.is_forced
+    }
+
+    /// Returns the `Rule`'s encoded target with placeholders replaced by
+    /// encoded path segments if it matches a decoded URI. Returns [`None`] if
+    /// the rule does not match.
+    pub fn apply(&self, uri: &str) -> Option<String> {
+        let mut segments = path_segments(uri);
+        let mut values = Vec::new();
+
+        for segment in &self.from {
+            let value = segments.next()?;
+
+            match segment {
+                Segment::Exact(name) if name == value => {}
+                Segment::Exact(_) => return None,
+                Segment::Placeholder(name) => values.push((name.as_str(), http::encode_uri(value))),
+            }
+        }
+
+        if self.is_splat {
+            let splat = segments.collect::<Vec<_>>().join("/");
+            values.push(("splat", http::encode_uri(&splat)));
+        } else if segments.next().is_some() {
+            return None;
+        }
+
+        // Longer names are replaced first so that a placeholder is not replaced
+        // by another placeholder that is its prefix.
+        values.sort_unstable_by_key(|(name, _)| Reverse(name.len()));
+        let mut target = self.to.clone();
+
+        for (name, value) in values {
+            target = target.replace(&format!(":{name}"), &value);
+        }
+
+        Some(target)
+    }
+}
+
+/// A segment of a [`Rule`]'s source pattern.
+enum Segment {
+    /// A segment that matches a path segment exactly.
+    Exact(String),
+
+    /// A named placeholder that matches any path segment.
+    Placeholder(String),
+}
+
+/// A [`Rule`] as a table in a config file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleTable {
+    /// The source pattern.
+    from: String,
+
+    /// The target URI or URL.
+    to: String,
+
+    /// The response status code.
+    status: Option<u16>,
+
+    /// Whether the rule applies even if a file exists at the request's URI.
+    force: Option<bool>,
+}
+
+impl TryFrom<RuleTable> for Rule {
+    type Error = String;
+
+    fn try_from(table: RuleTable) -> result::Result<Self, String> {
+        Self::try_new(
+            &table.from,
+            &table.to,
+            table.status.unwrap_or(301),
+            table.force.unwrap_or(false),
+        )
+    }
+}
+
+/// Loads [`Rule`]s from the redirect rules file in a root directory. Returns an
+/// empty [`Vec`] if the root directory does not contain a redirect rules file.
+pub fn try_load_rules(root: &Path) -> Result<Vec<Rule>> {
+    match fs::read_to_string(root.join(RULES_FILE_NAME)) {
+        Ok(source) => parse_rules(&source),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(Error::RedirectsRead(error)),
+    }
+}
+
+/// Parses [`Rule`]s from the source of a redirect rules file. Each line
+/// contains a source pattern, a target, and an optional status code followed
+/// by `!` if the rule is forced. Blank lines and comments starting with `#`
+/// are ignored.
+fn parse_rules(source: &str) -> Result<Vec<Rule>> {
+    let mut rules = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line = line.split_once('#').map_or(line, |(line, _)| line);
+        let parts: Vec<&str> = line.split_whitespace().collect();
+
+        let rule = match parts[..] {
+            [] => continue,
+            [from, to] => Rule::try_new(from, to, 301, false),
+            [from, to, status] => {
+                let (status, is_forced) = match status.strip_suffix('!') {
+                    None => (status, false),
+                    Some(status) => (status, true),
+                };
+
+                match status.parse() {
+                    Ok(status) => Rule::try_new(from, to, status, is_forced),
+                    Err(_) => Err(format!("status code '{status}' is not a number")),
+                }
+            }
+            [_] => Err("rule has no target".into()),
+            _ => Err("rule has too many fields".into()),
+        };
+
+        rules.push(rule.map_err(|message| Error::RedirectsInvalid {
+            line: index + 1,
+            message,
+        })?);
+    }
+
+    Ok(rules)
+}
+
+/// Returns an iterator over the non-empty segments of a path.
+fn path_segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
@@ -1,24 +1,179 @@
 use std::{
-    fs,
+    fs::File,
+    io::Read,
     path::{Path, PathBuf},
 };
 
 use crate::{
     error::{Error, Result},
-    http::{self, Request, Status},
-    page::Page,
+    http::{self, Encoding, Method, Request, Status},
+    page::{Asset, Page},
+    watch,
 };
 
+/// Returns whether an HTTP [`Request`] should open a live reload event stream.
+pub fn is_live_reload_request(request: &Request) -> bool {
+    request.config().is_watching()
+        && request.is_allowed()
+        && *request.method() == Method::Get
+        && request.uri() == watch::EVENTS_URI
+}
+
 /// Finds a [`Page`] to return as a response to an HTTP [`Request`].
 pub fn find_page(request: &Request) -> Page {
-    if !request.is_local() {
+    if !request.is_allowed() {
         return Page::Error(Status::Forbidden);
     }
 
+    let page = if !matches!(request.method(), Method::Get | Method::Head) {
+        Page::Error(Status::MethodNotAllowed)
+    } else if let Some(page) = find_redirect_page(request) {
+        page
+    } else if let Some(page) = find_clean_redirect_page(request) {
+        page
+    } else {
+        match find_file_page(request, request.uri()) {
+            Page::Error(Status::NotFound)
+                if let Some(fallback) = request.config().spa_fallback() =>
+            {
+                find_spa_fallback_page(request, fallback)
+            }
+            page => page,
+        }
+    };
+
+    match page {
+        Page::Error(status) => find_error_page(request, status),
+        page => page,
+    }
+}
+
+/// Finds an error [`Page`] for an HTTP [`Request`] with a response [`Status`]
+/// code. A custom error document named after the status code, such as
+/// `404.html`, is served from the error directory if it exists.
+fn find_error_page(request: &Request, status: Status) -> Page {
+    let config = request.config();
+    let name = format!("{}.html", status.code());
+
+    let uri = match config.error_dir() {
+        None => name,
+        Some(dir) => dir.join(name).to_string_lossy().into_owned(),
+    };
+
+    let Some(path) = resolve_path(config.root(), &uri).filter(|path| path.is_file()) else {
+        return Page::Error(status);
+    };
+
+    match open_asset(request, &path) {
+        Ok(asset) => Page::CustomError(status, asset),
+        Err(error) => {
+            error.print();
+            Page::Error(status)
+        }
+    }
+}
+
+/// Finds a [`Page`] for the first redirect or rewrite rule that matches an HTTP
+/// [`Request`]'s URI. Returns [`None`] if no rule applies.
+fn find_redirect_page(request: &Request) -> Option<Page> {
+    let uri = request.uri();
+    let mut is_shadowed = None;
+
+    for rule in request.config().redirect_rules() {
+        let Some(target) = rule.apply(uri) else {
+            continue;
+        };
+
+        // Rules that are not forced are shadowed by existing files.
+        if !rule.is_forced() && *is_shadowed.get_or_insert_with(|| is_file_uri(request, uri)) {
+            continue;
+        }
+
+        let rewrite_uri = || http::decode_uri(strip_query(&target));
+
+        let page = match rule.status() {
+            Status::Ok => find_file_page(request, &rewrite_uri()),
+            Status::NotFound => match find_file_page(request, &rewrite_uri()) {
+                Page::File(asset) => Page::CustomError(Status::NotFound, asset),
+                _ => Page::Error(Status::NotFound),
+            },
+            status => {
+                let mut target = target;
+
+                if !target.contains('?')
+                    && let Some(query) = request.query()
+                {
+                    target.push('?');
+                    target.push_str(query);
+                }
+
+                Page::Redirect(status, target)
+            }
+        };
+
+        return Some(page);
+    }
+
+    None
+}
+
+/// Returns whether a decoded URI resolves to a file or a directory with an
+/// index file for an HTTP [`Request`].
+fn is_file_uri(request: &Request, uri: &str) -> bool {
+    let config = request.config();
+
+    let path = resolve_path(config.root(), uri).or_else(|| {
+        config
+            .clean_url_extensions()
+            .and_then(|extensions| find_clean_url_path(config.root(), uri, extensions))
+    });
+
+    path.is_some_and(|path| {
+        path.is_file()
+            || path.is_dir()
+                && config
+                    .index_files()
+                    .iter()
+                    .any(|name| path.join(name).is_file())
+    })
+}
+
+/// Strips the query string from a URI.
+fn strip_query(uri: &str) -> &str {
+    uri.split_once('?').map_or(uri, |(uri, _)| uri)
+}
+
+/// Finds a redirection [`Page`] to the clean URL of an HTTP [`Request`]'s URI
+/// if it names a file with a clean URL extension. Returns [`None`] if the
+/// request should not be redirected.
+fn find_clean_redirect_page(request: &Request) -> Option<Page> {
     let config = request.config();
     let uri = request.uri();
 
-    let Some(mut path) = resolve_path(config.root(), uri) else {
+    if !config.is_redirecting_clean_urls() {
+        return None;
+    }
+
+    let extensions = config.clean_url_extensions()?;
+    let clean_uri = strip_clean_url_extension(uri, extensions, config.index_files())?;
+
+    resolve_path(config.root(), uri)
+        .is_some_and(|path| path.is_file())
+        .then(|| redirect_page(request, Status::Found, clean_uri))
+}
+
+/// Finds a [`Page`] for a file or directory resolved from a decoded URI for an
+/// HTTP [`Request`].
+fn find_file_page(request: &Request, uri: &str) -> Page {
+    let config = request.config();
+    let clean_url_extensions = config.clean_url_extensions();
+
+    let path = resolve_path(config.root(), uri).or_else(|| {
+        clean_url_extensions
+            .and_then(|extensions| find_clean_url_path(config.root(), uri, extensions))
+    });
+
+    let Some(mut path) = path else {
         return Page::Error(Status::NotFound);
     };
 
@@ -26,36 +181,173 @@ pub fn find_page(request: &Request) -> Page {
 
     if path.is_dir() {
         if !is_dir_uri {
-            let mut uri = http::encode_uri(uri);
-            uri.push('/');
-            return Page::Redirect(uri);
-        }
-
-        if config.is_serving_index_pages() {
-            return match list_dir(&path) {
-                Ok(names) => Page::Index(uri.into(), names),
-                Err(error) => error_page(&error),
-            };
+            return redirect_page(request, Status::Found, &format!("{uri}/"));
         }
 
-        path.push("index.html");
+        let index_path = config
+            .index_files()
+            .iter()
+            .map(|name| path.join(name))
+            .find(|path| path.is_file());
 
-        if !path.is_file() {
-            return Page::Error(Status::NotFound);
+        match index_path {
+            Some(index_path) => path = index_path,
+            None if config.is_serving_index_pages() => {
+                return match list_dir(&path) {
+                    Ok(names) => Page::Index(uri.into(), names),
+                    Err(error) => error_page(&error),
+                };
+            }
+            None => return Page::Error(Status::NotFound),
         }
     } else if is_dir_uri {
         return Page::Error(Status::NotFound);
     }
 
-    match fs::read(&path).map_err(Error::FileRead) {
-        Ok(contents) => {
-            let media_type = mime_guess::from_path(&path).first_raw();
-            Page::File(media_type, contents)
-        }
+    match open_asset(request, &path) {
+        Ok(asset) => Page::File(asset),
+        Err(error) => error_page(&error),
+    }
+}
+
+/// Returns a redirection [`Page`] with an HTTP response [`Status`] code to a
+/// decoded URI for an HTTP [`Request`]. The request's query string is
+/// preserved.
+fn redirect_page(request: &Request, status: Status, uri: &str) -> Page {
+    let mut uri = http::encode_uri(uri);
+
+    if let Some(query) = request.query() {
+        uri.push('?');
+        uri.push_str(query);
+    }
+
+    Page::Redirect(status, uri)
+}
+
+/// Strips a clean URL extension from a URI, such as `/about.html` to `/about`.
+/// URIs of index files are stripped to their directory, such as
+/// `/docs/index.html` to `/docs/`. Returns [`None`] if the URI does not end
+/// with a file name with one of the extensions.
+fn strip_clean_url_extension<'a>(
+    uri: &'a str,
+    extensions: &[String],
+    index_files: &[String],
+) -> Option<&'a str> {
+    let (stem, extension) = uri.rsplit_once('.')?;
+
+    if stem.ends_with('/') || !extensions.iter().any(|e| e.eq_ignore_ascii_case(extension)) {
+        return None;
+    }
+
+    let (dir, name) = uri.rsplit_once('/')?;
+
+    if index_files.iter().any(|index_file| index_file == name) {
+        return Some(&uri[..=dir.len()]);
+    }
+
+    Some(stem)
+}
+
+/// Finds a file [`Path`] for a clean URL, such as `about.html` for `/about`,
+/// by trying each extension in order. Returns [`None`] if no file was found.
+fn find_clean_url_path(root: &Path, uri: &str, extensions: &[String]) -> Option<PathBuf> {
+    if uri.ends_with('/') {
+        return None;
+    }
+
+    extensions
+        .iter()
+        .filter_map(|extension| resolve_path(root, &format!("{uri}.{extension}")))
+        .find(|path| path.is_file())
+}
+
+/// Finds a single-page application fallback [`Page`] from a fallback document
+/// path relative to the root directory for an HTTP [`Request`] that did not
+/// resolve. Only URIs without a file extension are routes that may fall back,
+/// so that missing assets are still not found.
+fn find_spa_fallback_page(request: &Request, fallback: &Path) -> Page {
+    let name = request.uri().rsplit('/').next().unwrap_or_default();
+
+    if name.contains('.') {
+        return Page::Error(Status::NotFound);
+    }
+
+    let Some(path) = resolve_path(request.config().root(), &fallback.to_string_lossy()) else {
+        return Page::Error(Status::NotFound);
+    };
+
+    if !path.is_file() {
+        return Page::Error(Status::NotFound);
+    }
+
+    match open_asset(request, &path) {
+        Ok(asset) => Page::File(asset),
         Err(error) => error_page(&error),
     }
 }
 
+/// Opens an [`Asset`] from a file [`Path`] for a [`Request`]. A precompressed
+/// sibling file is opened instead if its [`Encoding`] is accepted by the
+/// request. The media type is always inferred from the original path.
+fn open_asset(request: &Request, path: &Path) -> Result<Asset> {
+    let media_type = mime_guess::from_path(path).first_raw();
+
+    // HTML documents are not served precompressed while watching because the
+    // live reload script must be injected into them.
+    let is_live = request.config().is_watching() && media_type == Some("text/html");
+    let encodings = if is_live { &[][..] } else { &Encoding::ALL };
+    let mut encoded_path = None;
+    let mut is_encoding_negotiated = false;
+
+    for &encoding in encodings {
+        let Some(sibling) = find_encoded_sibling(request.config().root(), path, encoding) else {
+            continue;
+        };
+
+        is_encoding_negotiated = true;
+
+        if encoded_path.is_none() && request.accepts_encoding(encoding) {
+            encoded_path = Some((encoding, sibling));
+        }
+    }
+
+    let (encoding, file_path) = match &encoded_path {
+        None => (None, path),
+        Some((encoding, sibling)) => (Some(*encoding), sibling.as_path()),
+    };
+
+    let mut file = File::open(file_path).map_err(Error::FileRead)?;
+    let metadata = file.metadata().map_err(Error::FileRead)?;
+
+    let contents = if is_live {
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents).map_err(Error::FileRead)?;
+        Some(watch::inject_script(contents))
+    } else {
+        None
+    };
+
+    Ok(Asset {
+        media_type,
+        encoding,
+        is_encoding_negotiated,
+        file,
+        metadata,
+        contents,
+    })
+}
+
+/// Finds a sibling file of a file [`Path`] that is precompressed with an
+/// [`Encoding`]. Returns [`None`] if the sibling file is not an existing
+/// descendant of a root path.
+fn find_encoded_sibling(root: &Path, path: &Path, encoding: Encoding) -> Option<PathBuf> {
+    let mut sibling = path.as_os_str().to_owned();
+    sibling.push(".");
+    sibling.push(encoding.extension());
+    let sibling = Path::new(&sibling).canonicalize().ok()?;
+    (sibling.starts_with(root) && sibling.is_file()).then_some(sibling)
+}
+
 /// Resolves a path from a root [`Path`] and a URI. Returns [`None`] if the
 /// resolved path is not an existing descendant of the root path.
 fn resolve_path(root: &Path, uri: &str) -> Option<PathBuf> {
@@ -0,0 +1,235 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Condvar, Mutex, PoisonError,
+        mpsc::{self, Receiver, RecvTimeoutError},
+    },
+    thread,
+    time::Duration,
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+
+use crate::{
+    error::{Error, Result},
+    http::EventStream,
+};
+
+/// The URI of the live reload event stream.
+pub const EVENTS_URI: &str = "/.holo/live-reload";
+
+/// The duration to wait for more file system events before notifying clients
+/// of a change. Saving or rebuilding a file often causes several events.
+const DEBOUNCE_DURATION: Duration = Duration::from_millis(100);
+
+/// The interval between keep-alive messages sent to idle clients. This allows
+/// disconnected clients to be detected.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A kind of change to the files in the root directory.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Change {
+    /// A change to stylesheets only.
+    Style,
+
+    /// A change to any other files.
+    Page,
+}
+
+impl Change {
+    /// Returns the `Change` caused by a file system [`Event`]. Returns [`None`]
+    /// if the event does not change any files other than ignored files.
+    fn from_event(event: &Event, ignored_paths: &[PathBuf]) -> Option<Self> {
+        if matches!(event.kind, EventKind::Access(_))
+            || event.paths.iter().all(|path| ignored_paths.contains(path))
+        {
+            return None;
+        }
+
+        let is_style = event.paths.iter().all(|path| {
+            path.extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("css"))
+        });
+
+        Some(if is_style { Self::Style } else { Self::Page })
+    }
+
+    /// Returns the `Change`'s event name for live reload clients.
+    fn event_name(self) -> &'static str {
+        match self {
+            Self::Style => "style",
+            Self::Page => "reload",
+        }
+    }
+}
+
+/// The most recent [`Change`] shared between threads.
+struct Changes {
+    /// The number of changes so far and the most recent [`Change`].
+    state: Mutex<(u64, Change)>,
+
+    /// The [`Condvar`] for waiting on a new [`Change`].
+    condvar: Condvar,
+}
+
+impl Changes {
+    /// Returns the number of changes so far.
+    fn count(&self) -> u64 {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).0
+    }
+
+    /// Publishes a new [`Change`] to every waiting thread.
+    fn publish(&self, change: Change) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        *state = (state.0 + 1, change);
+        self.condvar.notify_all();
+    }
+
+    /// Waits for a [`Change`] after a number of changes for up to a timeout.
+    /// Returns the new number of changes and the most recent change, or
+    /// [`None`] if the wait timed out.
+    fn wait(&self, count: u64, timeout: Duration) -> Option<(u64, Change)> {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let (state, _) = self
+            .condvar
+            .wait_timeout_while(state, timeout, |state| state.0 == count)
+            .unwrap_or_else(PoisonError::into_inner);
+
+        (state.0 != count).then_some(*state)
+    }
+}
+
+/// A file watcher that notifies live reload clients of changes to the root
+/// directory.
+pub struct Watcher {
+    /// The underlying [`RecommendedWatcher`], which stops watching when it is
+    /// dropped.
+    _watcher: RecommendedWatcher,
+
+    /// The [`Changes`] shared with the live reload clients.
+    changes: Arc<Changes>,
+}
+
+impl Watcher {
+    /// Creates a new `Watcher` from a root directory [`Path`] and the paths to
+    /// files that are written by Holo and should not cause changes.
+    pub fn try_new(root: &Path, ignored_paths: &[PathBuf]) -> Result<Self> {
+        let ignored_paths: Vec<_> = ignored_paths
+            .iter()
+            .filter_map(|path| canonicalize_file(path))
+            .collect();
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(Error::Watch)?;
+
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .map_err(Error::Watch)?;
+
+        let changes = Arc::new(Changes {
+            state: Mutex::new((0, Change::Page)),
+            condvar: Condvar::new(),
+        });
+
+        thread::spawn({
+            let changes = Arc::clone(&changes);
+            move || run_debouncer(&receiver, &changes, &ignored_paths)
+        });
+
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+        })
+    }
+
+    /// Subscribes an [`EventStream`] to changes on a new thread. The thread
+    /// stops when the client disconnects.
+    pub fn subscribe(&self, stream: EventStream) {
+        let changes = Arc::clone(&self.changes);
+        thread::spawn(move || run_subscriber(stream, &changes));
+    }
+}
+
+/// Injects the live reload client script into an HTML document. The script is
+/// inserted before the closing body tag, or at the end of the document if
+/// there is none.
+pub fn inject_script(mut html: Vec<u8>) -> Vec<u8> {
+    static SCRIPT: &str = include_str!("../res/live-reload.html");
+    let script = SCRIPT.replace("{{uri}}", EVENTS_URI);
+
+    let index = html
+        .windows(b"</body".len())
+        .rposition(|window| window.eq_ignore_ascii_case(b"</body"))
+        .unwrap_or(html.len());
+
+    html.splice(index..index, script.into_bytes());
+    html
+}
+
+/// Returns the canonical path to a file that may not exist yet. Returns
+/// [`None`] if the file's parent directory does not exist.
+fn canonicalize_file(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?;
+
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    parent.canonicalize().ok().map(|parent| parent.join(name))
+}
+
+/// Receives file system [`Event`]s and publishes them to [`Changes`] after
+/// they stop arriving for a short duration. Events for ignored paths are
+/// discarded.
+fn run_debouncer(
+    receiver: &Receiver<notify::Result<Event>>,
+    changes: &Changes,
+    ignored_paths: &[PathBuf],
+) {
+    while let Ok(event) = receiver.recv() {
+        let mut change = None;
+        let mut event = event;
+
+        loop {
+            if let Ok(event) = &event
+                && let Some(event_change) = Change::from_event(event, ignored_paths)
+            {
+                change = match change {
+                    None | Some(Change::Style) => Some(event_change),
+                    Some(Change::Page) => Some(Change::Page),
+                };
+            }
+
+            event = match receiver.recv_timeout(DEBOUNCE_DURATION) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+        }
+
+        if let Some(change) = change {
+            changes.publish(change);
+        }
+    }
+}
+
+/// Sends [`Changes`] to an [`EventStream`] until the client disconnects.
+fn run_subscriber(mut stream: EventStream, changes: &Changes) {
+    let mut count = changes.count();
+
+    loop {
+        let result = match changes.wait(count, KEEP_ALIVE_INTERVAL) {
+            Some((new_count, change)) => {
+                count = new_count;
+                stream.try_send(change.event_name())
+            }
+            None => stream.try_keep_alive(),
+        };
+
+        if result.is_err() {
+            break;
+        }
+    }
+}